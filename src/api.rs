@@ -1,5 +1,11 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::{Error, Io, Kind, MapCrateError};
 
 /// This data comes from doing an HTTP request to ArangoDB:
 ///
@@ -10,6 +16,16 @@ use serde_json::Value as JsonValue;
 #[derive(Serialize, Deserialize)]
 pub(crate) struct LoggerStateData {
 	pub(crate) state: LoggerState,
+	#[serde(default)]
+	pub(crate) server: Option<LoggerServer>,
+}
+
+/// Server property coming from [`LoggerStateData`]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LoggerServer {
+	pub(crate) version: String,
+	#[serde(rename = "serverId")]
+	pub(crate) server_id: String,
 }
 
 /// State property coming from [`LoggerStateData`]
@@ -25,8 +41,130 @@ pub(crate) struct LoggerState {
 	pub(crate) time: String,
 }
 
-/// All log types supported for ArangoDB replication API
+/// This data comes from doing an HTTP request to ArangoDB:
+///
+/// **`POST /_open/auth`**
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AuthResponse {
+	pub(crate) jwt: String,
+}
+
+/// This data comes from doing an HTTP request to ArangoDB:
+///
+/// **`GET /_api/cluster/endpoints`**
+///
+/// On Active Failover deployments the first endpoint is the current leader
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ClusterEndpoints {
+	pub(crate) endpoints: Vec<ClusterEndpoint>,
+}
+
+/// Endpoint coming from [`ClusterEndpoints`], e.g. `tcp://10.0.0.1:8529`
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ClusterEndpoint {
+	pub(crate) endpoint: String,
+}
+
+impl ClusterEndpoint {
+	/// Returns the endpoint as an HTTP host, or [`None`] for non TCP endpoints like Unix sockets
+	///
+	/// returns: `Option<String>`
+	pub(crate) fn host(&self) -> Option<String> {
+		let (scheme, address) = self.endpoint.split_once("://")?;
+
+		match scheme {
+			"tcp" | "http+tcp" => Some(format!("http://{}", address)),
+			"ssl" | "http+ssl" => Some(format!("https://{}", address)),
+			_ => None,
+		}
+	}
+}
+
+/// This data comes from doing an HTTP request to ArangoDB:
+///
+/// **`POST /_api/cursor`**
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CursorResponse {
+	pub(crate) result: Vec<JsonValue>,
+	#[serde(rename = "hasMore", default)]
+	pub(crate) has_more: bool,
+	#[serde(default)]
+	pub(crate) id: Option<String>,
+	/// The id of the next batch, only sent by ArangoDB 3.11 and later when the cursor allows
+	/// retries
+	#[serde(rename = "nextBatchId", default)]
+	pub(crate) next_batch_id: Option<JsonValue>,
+}
+
+impl CursorResponse {
+	/// Returns the id of the next batch as a string, if any
+	///
+	/// returns: `Option<String>`
+	pub(crate) fn next_batch(&self) -> Option<String> {
+		match self.next_batch_id.as_ref()? {
+			JsonValue::String(id) => Some(id.clone()),
+			id => Some(id.to_string()),
+		}
+	}
+}
+
+/// This data comes from doing an HTTP request to ArangoDB:
+///
+/// **`GET /_api/collection`**
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CollectionsResponse {
+	pub(crate) result: Vec<CollectionInfo>,
+}
+
+/// Collection coming from [`CollectionsResponse`]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CollectionInfo {
+	pub(crate) name: String,
+	#[serde(rename = "globallyUniqueId")]
+	pub(crate) globally_unique_id: String,
+}
+
+/// This data comes from doing an HTTP request to ArangoDB, as an array of one entry per WAL file:
+///
+/// **`GET /_api/replication/logger-tick-ranges`**
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TickRange {
+	#[serde(rename = "tickMin")]
+	pub(crate) tick_min: String,
+	#[serde(rename = "tickMax")]
+	pub(crate) tick_max: String,
+}
+
+/// This data comes from doing an HTTP request to ArangoDB:
+///
+/// **`POST /_api/replication/barrier`**
 #[derive(Serialize, Deserialize)]
+pub(crate) struct BarrierResponse {
+	/// The barrier id, sent as a string or as a number depending on the server version
+	pub(crate) id: JsonValue,
+}
+
+impl BarrierResponse {
+	/// Returns the barrier id as a string
+	///
+	/// returns: [`String`]
+	pub(crate) fn id(&self) -> String {
+		match &self.id {
+			JsonValue::String(id) => id.clone(),
+			id => id.to_string(),
+		}
+	}
+}
+
+/// Body of the non-successful responses of the ArangoDB HTTP API
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ErrorResponse {
+	#[serde(rename = "errorNum")]
+	pub(crate) error_num: u32,
+}
+
+/// All log types supported for ArangoDB replication API
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub(crate) enum LogType {
 	/// Create a database
 	CreateDatabase = 1100,
@@ -94,12 +232,401 @@ impl TryFrom<u16> for LogType {
 /// JSON structure for [`LogType::InsertOrReplaceDocument`] and [`LogType::RemoveDocument`] log
 /// types coming from doing an HTTP request to ArangoDB:
 ///
-/// **`GET /_api/replication/logger-follow`**
+/// **`GET /_api/replication/logger-follow`** or **`GET /_api/wal/tail`**
 ///
-/// This data is then gonna be dispatched to event handlers
-#[derive(Serialize, Deserialize, Debug)]
+/// This data is then gonna be dispatched to event handlers. The document data is kept as raw JSON
+/// and only parsed when accessed, so operations discarded by filters don't pay for it
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DocumentOperation {
-	#[serde(rename = "cname")]
+	/// The collection name. **`wal/tail`** lines only name the collection by its `cuid`, which
+	/// the [`Trigger`](`crate::Trigger`) resolves to its name
+	#[serde(rename = "cname", default)]
 	pub collection: String,
+	#[serde(default, skip_serializing)]
+	cuid: Option<String>,
+	#[serde(rename = "data")]
+	raw: Box<RawValue>,
+	#[serde(skip)]
+	data: OnceLock<std::result::Result<JsonValue, String>>,
+}
+
+impl DocumentOperation {
+	/// Creates a new [`DocumentOperation`] from already parsed data
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The collection name
+	/// * `data`: The operation data
+	///
+	/// returns: [`DocumentOperation`]
+	pub(crate) fn from_value(collection: String, data: JsonValue) -> Self {
+		Self {
+			collection,
+			cuid: None,
+			raw: serde_json::value::to_raw_value(&data)
+				.expect("JSON values always serialize to raw JSON"),
+			data: OnceLock::from(Ok(data)),
+		}
+	}
+
+	/// Returns the operation data, parsing it on the first access
+	///
+	/// returns: `Result<&JsonValue>`, failing if the data can't be parsed, e.g. when it nests
+	/// deeper than the JSON parser allows
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	///
+	/// fn get_key(doc: &DocumentOperation) -> Option<&str> {
+	///     doc.data().ok()?.get("_key").and_then(|v| v.as_str())
+	/// }
+	/// ```
+	pub fn data(&self) -> crate::Result<&JsonValue> {
+		self.data
+			.get_or_init(|| serde_json::from_str(self.raw.get()).map_err(|e| e.to_string()))
+			.as_ref()
+			.map_err(|e| Error::new(Kind::Io(Io::Serialize)).with(e.clone()))
+	}
+
+	/// Returns a field of the operation data given its dot separated path, e.g. `customer.id`
+	///
+	/// # Arguments
+	///
+	/// * `path`: The field path
+	///
+	/// returns: `Option<&JsonValue>`, [`None`] if the field is missing or the data can't be parsed
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	///
+	/// fn get_customer_id(doc: &DocumentOperation) -> Option<&str> {
+	///     doc.field("customer.id").and_then(|v| v.as_str())
+	/// }
+	/// ```
+	pub fn field(&self, path: &str) -> Option<&JsonValue> {
+		path.split('.')
+			.try_fold(self.data().ok()?, |value, segment| value.get(segment))
+	}
+
+	/// Returns the globally unique id of the collection, only sent by **`wal/tail`**
+	///
+	/// returns: `Option<&str>`
+	pub(crate) fn cuid(&self) -> Option<&str> {
+		self.cuid.as_deref()
+	}
+
+	/// Returns the operation data as the raw JSON received from the server, without parsing it
+	///
+	/// returns: `&str`
+	pub fn raw_data(&self) -> &str {
+		self.raw.get()
+	}
+
+	/// Deserializes the operation data straight from the raw JSON into a typed struct, skipping
+	/// the intermediate [`JsonValue`]
+	///
+	/// returns: `Result<T>`
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use serde::Deserialize;
+	///
+	/// #[derive(Deserialize)]
+	/// struct Account {
+	///     #[serde(rename = "_key")]
+	///     key: String,
+	///     email: String,
+	/// }
+	///
+	/// fn get_account(doc: &DocumentOperation) -> Option<Account> {
+	///     doc.deserialize_data().ok()
+	/// }
+	/// ```
+	pub fn deserialize_data<T: DeserializeOwned>(&self) -> crate::Result<T> {
+		serde_json::from_str(self.raw.get()).map_crate_err()
+	}
+}
+
+/// JSON structure for every DDL log type (databases, collections, indexes and views) coming from
+/// doing an HTTP request to ArangoDB:
+///
+/// **`GET /_api/replication/logger-follow`** or **`GET /_api/wal/tail`**
+#[derive(Serialize, Deserialize)]
+pub(crate) struct DdlLog {
+	pub(crate) cname: Option<String>,
+	/// The globally unique id of the collection, only sent by **`wal/tail`**
+	pub(crate) cuid: Option<String>,
+	#[serde(default)]
+	pub(crate) data: JsonValue,
+}
+
+impl DdlLog {
+	/// Returns a string property of the log data
+	fn data_str(&self, key: &str) -> Option<String> {
+		self.data
+			.get(key)
+			.and_then(JsonValue::as_str)
+			.map(String::from)
+	}
+}
+
+/// Names of the collections of a database indexed by their globally unique id (`cuid`), the only
+/// collection reference of **`GET /_api/wal/tail`** lines. Built from
+/// **`GET /_api/collection`** and kept up to date with the collection DDL lines of the log
+#[derive(Default)]
+pub(crate) struct CollectionNames {
+	names: HashMap<String, String>,
+}
+
+impl CollectionNames {
+	/// Creates a new [`CollectionNames`] from the collections of a [`CollectionsResponse`]
+	///
+	/// # Arguments
+	///
+	/// * `collections`: The database collections
+	///
+	/// returns: [`CollectionNames`]
+	pub(crate) fn new(collections: Vec<CollectionInfo>) -> Self {
+		Self {
+			names: collections
+				.into_iter()
+				.map(|c| (c.globally_unique_id, c.name))
+				.collect(),
+		}
+	}
+
+	/// Returns the name of the collection with the given `cuid`, if known
+	///
+	/// returns: `Option<String>`
+	fn get(&self, cuid: Option<&str>) -> Option<String> {
+		cuid.and_then(|c| self.names.get(c)).cloned()
+	}
+
+	/// Applies a collection DDL operation, the collection name being the new one for renames
+	fn apply(&mut self, kind: DdlKind, cuid: Option<&str>, name: Option<&str>) {
+		let cuid = match cuid {
+			Some(cuid) => cuid.to_string(),
+			None => return,
+		};
+
+		match (kind, name) {
+			(DdlKind::Drop, _) => {
+				self.names.remove(&cuid);
+			}
+			(DdlKind::Create | DdlKind::Rename, Some(name)) => {
+				self.names.insert(cuid, name.to_string());
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Kind of a DDL operation carried by an [`Event`]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DdlKind {
+	/// The database, collection, index or view got created
+	Create,
+	/// The database, collection, index or view got dropped
+	Drop,
+	/// The collection got renamed
+	Rename,
+	/// The collection or view properties changed
+	Change,
+	/// The collection got truncated
+	Truncate,
+}
+
+/// Collection DDL operation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CollectionOperation {
+	pub kind: DdlKind,
+	/// The collection name. For [`DdlKind::Rename`] operations this is the old collection name
+	pub collection: Option<String>,
+	pub data: JsonValue,
+}
+
+impl CollectionOperation {
+	/// Returns the new collection name of a [`DdlKind::Rename`] operation
+	///
+	/// returns: `Option<&str>`
+	pub fn new_name(&self) -> Option<&str> {
+		match self.kind {
+			DdlKind::Rename => self.data.get("name").and_then(JsonValue::as_str),
+			_ => None,
+		}
+	}
+}
+
+/// Database DDL operation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DatabaseOperation {
+	pub kind: DdlKind,
+	pub database: Option<String>,
 	pub data: JsonValue,
 }
+
+/// Index DDL operation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexOperation {
+	pub kind: DdlKind,
+	pub collection: Option<String>,
+	pub data: JsonValue,
+}
+
+/// View DDL operation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ViewOperation {
+	pub kind: DdlKind,
+	pub view: Option<String>,
+	pub data: JsonValue,
+}
+
+/// Any event coming from the ArangoDB replication log. Transaction markers are not events, as the
+/// document operations of a transaction are only emitted once it gets committed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", content = "operation", rename_all = "camelCase")]
+pub enum Event {
+	/// A document got inserted or replaced
+	InsertOrReplace(DocumentOperation),
+	/// A document got removed
+	Remove(DocumentOperation),
+	/// A collection got created, dropped, renamed, changed or truncated
+	Collection(CollectionOperation),
+	/// A database got created or dropped
+	Database(DatabaseOperation),
+	/// An index got created or dropped
+	Index(IndexOperation),
+	/// A view got created, dropped or changed
+	View(ViewOperation),
+	/// The server had no new events to deliver
+	Heartbeat,
+}
+
+impl Event {
+	/// Parses a non-transactional log line into an [`Event`]. Collections only referenced by their
+	/// `cuid` are named from the given [`CollectionNames`], which collection DDL lines update.
+	/// Document operations on unknown collections are left with an empty collection name
+	///
+	/// # Arguments
+	///
+	/// * `log_type`: The [`LogType`] of the line
+	/// * `line`: The log line
+	/// * `names`: The [`CollectionNames`] of the database
+	///
+	/// returns: `Result<Option<Event>>`, [`None`] for transaction markers
+	pub(crate) fn parse(
+		log_type: LogType,
+		line: &str,
+		names: &mut CollectionNames,
+	) -> crate::Result<Option<Event>> {
+		let parse_document = |line: &str| -> crate::Result<DocumentOperation> {
+			let mut doc: DocumentOperation = serde_json::from_str(line).map_crate_err()?;
+
+			if doc.collection.is_empty() {
+				if let Some(name) = names.get(doc.cuid.as_deref()) {
+					doc.collection = name;
+				}
+			}
+
+			Ok(doc)
+		};
+
+		let event = match log_type {
+			LogType::InsertOrReplaceDocument => Event::InsertOrReplace(parse_document(line)?),
+			LogType::RemoveDocument => Event::Remove(parse_document(line)?),
+			LogType::StartTransaction | LogType::CommitTransaction | LogType::AbortTransaction => {
+				return Ok(None)
+			}
+			ddl => {
+				let log: DdlLog = serde_json::from_str(line).map_crate_err()?;
+
+				match ddl {
+					LogType::CreateDatabase | LogType::DropDatabase => {
+						Event::Database(DatabaseOperation {
+							kind: if matches!(ddl, LogType::CreateDatabase) {
+								DdlKind::Create
+							} else {
+								DdlKind::Drop
+							},
+							database: log.data_str("name"),
+							data: log.data,
+						})
+					}
+					LogType::CreateIndex | LogType::DropIndex => Event::Index(IndexOperation {
+						kind: if matches!(ddl, LogType::CreateIndex) {
+							DdlKind::Create
+						} else {
+							DdlKind::Drop
+						},
+						collection: log.cname.or_else(|| names.get(log.cuid.as_deref())),
+						data: log.data,
+					}),
+					LogType::CreateView | LogType::DropView | LogType::ChangeView => {
+						Event::View(ViewOperation {
+							kind: match ddl {
+								LogType::CreateView => DdlKind::Create,
+								LogType::DropView => DdlKind::Drop,
+								_ => DdlKind::Change,
+							},
+							view: log.data_str("name"),
+							data: log.data,
+						})
+					}
+					_ => {
+						let kind = match ddl {
+							LogType::CreateCollection => DdlKind::Create,
+							LogType::DropCollection => DdlKind::Drop,
+							LogType::RenameCollection => DdlKind::Rename,
+							LogType::TruncateCollection => DdlKind::Truncate,
+							_ => DdlKind::Change,
+						};
+
+						// Depending on the server version the collection name comes either on the
+						// `cname` property or on the log data, for renames the log data holds
+						// the new name so the old one comes on `oldName`
+						let collection = if kind == DdlKind::Rename {
+							log.data_str("oldName").or_else(|| log.cname.clone())
+						} else {
+							log.cname.clone().or_else(|| log.data_str("name"))
+						}
+						.or_else(|| names.get(log.cuid.as_deref()));
+
+						let name = match kind {
+							DdlKind::Rename => log.data_str("name"),
+							_ => collection.clone(),
+						};
+
+						names.apply(kind, log.cuid.as_deref(), name.as_deref());
+
+						Event::Collection(CollectionOperation {
+							kind,
+							collection,
+							data: log.data,
+						})
+					}
+				}
+			}
+		};
+
+		Ok(Some(event))
+	}
+
+	/// Returns the name of the collection affected by the event, if any
+	///
+	/// returns: `Option<&str>`
+	pub fn collection(&self) -> Option<&str> {
+		match self {
+			Event::InsertOrReplace(doc) | Event::Remove(doc) => Some(doc.collection.as_str()),
+			Event::Collection(op) => op.collection.as_deref(),
+			Event::Index(op) => op.collection.as_deref(),
+			_ => None,
+		}
+	}
+}
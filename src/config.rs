@@ -0,0 +1,103 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::{Error, Io, Kind, MapCrateError, Result};
+
+/// Live view of the documents of a configuration collection, indexed by their `_key` and decoded
+/// into `T`, see [`Trigger::watch_config`]. It's cheap to clone, so it can be placed on the
+/// contexts of the handlers that need it, e.g. to read feature flags stored in ArangoDB.
+///
+/// [`Trigger::watch_config`]: crate::Trigger::watch_config
+pub struct ConfigStore<T> {
+	entries: Arc<RwLock<HashMap<String, Arc<T>>>>,
+}
+
+impl<T> ConfigStore<T> {
+	/// Creates a new empty [`ConfigStore`]
+	pub(crate) fn new() -> Self {
+		Self {
+			entries: Arc::new(RwLock::new(HashMap::new())),
+		}
+	}
+
+	/// Returns the document with the given key, if any
+	///
+	/// # Arguments
+	///
+	/// * `key`: The document `_key`
+	///
+	/// returns: `Option<Arc<T>>`
+	pub fn get(&self, key: &str) -> Option<Arc<T>> {
+		self.entries.read().unwrap().get(key).cloned()
+	}
+
+	/// Returns a snapshot of all the documents indexed by their key
+	///
+	/// returns: `HashMap<String, Arc<T>>`
+	pub fn entries(&self) -> HashMap<String, Arc<T>> {
+		self.entries.read().unwrap().clone()
+	}
+
+	/// Returns the number of documents
+	///
+	/// returns: `usize`
+	pub fn len(&self) -> usize {
+		self.entries.read().unwrap().len()
+	}
+
+	/// Returns whether there are no documents
+	///
+	/// returns: `bool`
+	pub fn is_empty(&self) -> bool {
+		self.entries.read().unwrap().is_empty()
+	}
+}
+
+impl<T> Clone for ConfigStore<T> {
+	fn clone(&self) -> Self {
+		Self {
+			entries: Arc::clone(&self.entries),
+		}
+	}
+}
+
+/// Type erased [`ConfigStore`] kept by the [`Trigger`](`crate::Trigger`) to apply the changes of
+/// the watched collection
+pub(crate) trait ConfigWatch: Send + Sync {
+	/// Decodes and stores a document, replacing the previous version if any
+	fn save(&self, data: &JsonValue) -> Result<()>;
+
+	/// Removes the document with the given key
+	fn remove(&self, key: &str);
+
+	/// Removes all the documents
+	fn clear(&self);
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> ConfigWatch for ConfigStore<T> {
+	fn save(&self, data: &JsonValue) -> Result<()> {
+		let key = data
+			.get("_key")
+			.and_then(JsonValue::as_str)
+			.ok_or(Error::new(Kind::Io(Io::Serialize)))?;
+
+		let value = T::deserialize(data).map_crate_err()?;
+
+		self.entries
+			.write()
+			.unwrap()
+			.insert(key.to_string(), Arc::new(value));
+
+		Ok(())
+	}
+
+	fn remove(&self, key: &str) {
+		self.entries.write().unwrap().remove(key);
+	}
+
+	fn clear(&self) {
+		self.entries.write().unwrap().clear();
+	}
+}
@@ -1,19 +1,28 @@
-use futures_util::stream::IntoAsyncRead;
-use futures_util::{AsyncBufReadExt, AsyncRead, Stream, TryStreamExt};
+#[cfg(feature = "gzip")]
+use async_compression::futures::bufread::GzipDecoder;
+#[cfg(feature = "gzip")]
+use futures_util::io::BufReader;
+use futures_util::{AsyncBufRead, AsyncBufReadExt, AsyncRead, Stream, TryStreamExt};
 use hyper::body::Bytes;
-use hyper::Body;
+use hyper::{Body, Response};
 
 use std::pin::Pin;
 use std::result::Result as StdResult;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
-use crate::{MapCrateError, Result};
+use crate::{Error, Kind, MapCrateError, Result};
 
 /// Line deserializer for [`AsyncRead`] trait
 pub(crate) struct Deserializer<R: AsyncRead> {
 	inner: R,
+	read: u64,
+	timeout: Option<Duration>,
 }
 
+/// Buffered reader of a response body, decompressing it if needed
+pub(crate) type BodyReader = Box<dyn AsyncBufRead + Send + Unpin>;
+
 /// Wrapper for [`Body`] to implement [`Stream`] trait with Std Error as Result to help typings and
 /// prevent error mapping on the [`Deserializer`] constructor
 pub(crate) struct BodyStream {
@@ -39,38 +48,89 @@ impl Stream for BodyStream {
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
 		Pin::new(&mut self.inner)
 			.poll_next(cx)
-			.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{}", e)))
+			.map_err(|e| std::io::Error::other(format!("{}", e)))
 	}
 }
 
-impl Deserializer<IntoAsyncRead<BodyStream>> {
+impl Deserializer<BodyReader> {
 	/// Creates a new Deserializer instance with which you'll be able to read lines in a Streamed way
-	/// for [`Body`] HTTP responses.
+	/// for [`Body`] HTTP responses. With the `gzip` feature, bodies sent with
+	/// `Content-Encoding: gzip` are decompressed on the fly
 	///
 	/// # Arguments
 	///
-	/// * `body`: The response body to be deserialized
+	/// * `response`: The response whose body is to be deserialized
 	///
-	/// returns: [`Deserializer<IntoAsyncRead<BodyStream>>`]
-	pub(crate) fn new(body: Body) -> Self {
+	/// returns: [`Deserializer<BodyReader>`]
+	pub(crate) fn new(response: Response<Body>) -> Self {
+		#[cfg(feature = "gzip")]
+		let gzip = response
+			.headers()
+			.get(hyper::header::CONTENT_ENCODING)
+			.map(|v| v.as_bytes().eq_ignore_ascii_case(b"gzip"))
+			.unwrap_or(false);
+
+		let body = BodyStream::new(response.into_body()).into_async_read();
+
+		#[cfg(feature = "gzip")]
+		let inner: BodyReader = if gzip {
+			Box::new(BufReader::new(GzipDecoder::new(body)))
+		} else {
+			Box::new(body)
+		};
+
+		#[cfg(not(feature = "gzip"))]
+		let inner: BodyReader = Box::new(body);
+
 		Self {
-			inner: BodyStream::new(body).into_async_read(),
+			inner,
+			read: 0,
+			timeout: None,
 		}
 	}
 
+	/// Sets the maximum time to wait for each line
+	///
+	/// # Arguments
+	///
+	/// * `timeout`: The line timeout, or [`None`] to wait forever
+	///
+	/// returns: [`Deserializer<BodyReader>`]
+	pub(crate) fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+		self.timeout = timeout;
+		self
+	}
+
 	/// Reads a new line from the Asynchronous [`BodyStream`] wrapper returning [`None`] if there
 	/// are no more lines to read
 	///
-	/// returns: [`Result<Option<String>>`]
+	/// returns: [`Result<Option<String>>`], failing if the line isn't read within the timeout
 	pub(crate) async fn read_line(&mut self) -> Result<Option<String>> {
 		let mut str = String::new();
 
-		let b = self.inner.read_line(&mut str).await.map_crate_err()?;
+		let read = self.inner.read_line(&mut str);
+
+		let b = match self.timeout {
+			Some(timeout) => tokio::time::timeout(timeout, read)
+				.await
+				.map_err(|_| Error::new(Kind::Timeout))?,
+			None => read.await,
+		}
+		.map_crate_err()?;
 
 		if b == 0 {
 			return Ok(None);
 		}
 
+		self.read += b as u64;
+
 		Ok(Some(str))
 	}
+
+	/// Returns the number of bytes read so far, after decompression
+	///
+	/// returns: `u64`
+	pub(crate) fn bytes_read(&self) -> u64 {
+		self.read
+	}
 }
@@ -1,10 +1,12 @@
 use std::fmt;
 use std::result::Result as StdResult;
 
+use crate::DiagnosticCategory;
+
 /// A `Result` typedef to use with the [`crate::Error`] type
 pub type Result<T> = StdResult<T, Error>;
 
-type Cause = Box<dyn std::error::Error>;
+type Cause = Box<dyn std::error::Error + Send + Sync>;
 
 /// A generic "error" for `arangodb_events_rs` crate
 ///
@@ -28,6 +30,13 @@ pub(super) enum Kind {
 	ArangoDB(ArangoDBError),
 	/// I/O Errors
 	Io(Io),
+	/// Errors while subscribing a [`Handler`](`crate::Handler`) to the Trigger
+	Subscription(SubscriptionError),
+	/// The ArangoDB server didn't respond within the configured timeout
+	Timeout,
+	/// Invalid TLS configuration. It'll have attached the original error for more details
+	#[cfg(feature = "tls")]
+	Tls,
 }
 
 #[derive(Debug)]
@@ -38,10 +47,39 @@ pub(super) enum Io {
 	Other,
 }
 
+#[derive(Debug)]
+pub(super) enum SubscriptionError {
+	/// The same handler is already subscribed to the same event and scope, and the
+	/// [`DuplicatePolicy`](`crate::DuplicatePolicy`) in use is `Error`. It'll have attached the
+	/// handler type name
+	Duplicate,
+	/// The given [`HandlerContext`](`crate::HandlerContext`) type is not the
+	/// [`Handler::Context`](`crate::Handler::Context`) one. It'll have attached the handler type
+	/// name
+	ContextMismatch,
+}
+
 #[derive(Debug)]
 pub(super) enum ArangoDBError {
 	/// HTTP non-successful requests, returning `4xx` or `5xx` HTTP Status codes
 	HttpError(hyper::StatusCode),
+	/// The replication logger of the server is not running, or the server doesn't support the
+	/// replication API at all, like cluster Coordinators do
+	ReplicationUnavailable,
+	/// The [`ResumeToken`](`crate::ResumeToken`) given to the Trigger belongs to another server
+	/// than the one it connects to
+	ServerMismatch,
+	/// The server log advanced beyond the [`StartupProbe`](`crate::StartupProbe`) limit between
+	/// the Trigger initialization and its first `listen()` call
+	StartupDrift,
+	/// The Trigger database doesn't exist on the server, e.g. because it was dropped
+	DatabaseNotFound,
+	/// The server version doesn't support a feature the Trigger is configured to use. It'll have
+	/// attached the feature and the server version
+	UnsupportedServer,
+	/// The tick the Trigger reads the server log from is no longer available on the server, which
+	/// already pruned the following log entries. It'll have attached the tick
+	TickExpired,
 }
 
 /// Trait to add [`map_crate_err`] method for all implementations that converts external error
@@ -84,17 +122,89 @@ impl Error {
 		self
 	}
 
+	/// Returns whether the error comes from the Trigger database not existing on the server
+	///
+	/// returns: `bool`
+	pub(crate) fn is_database_not_found(&self) -> bool {
+		matches!(
+			self.inner.kind,
+			Kind::ArangoDB(ArangoDBError::DatabaseNotFound)
+		)
+	}
+
+	/// Returns whether the error comes from the server log being pruned beyond the tick the
+	/// [`Trigger`](`crate::Trigger`) reads from, meaning events were lost. Consumers usually
+	/// recover by rebuilding their state from a full sync and initializing the Trigger again
+	/// without a [`ResumeToken`](`crate::ResumeToken`)
+	///
+	/// returns: `bool`
+	pub fn is_tick_expired(&self) -> bool {
+		matches!(self.inner.kind, Kind::ArangoDB(ArangoDBError::TickExpired))
+	}
+
+	/// Returns whether the error comes from the ArangoDB server not being reachable or not
+	/// responding in time
+	///
+	/// returns: `bool`
+	pub(crate) fn is_connection_error(&self) -> bool {
+		matches!(self.inner.kind, Kind::Http | Kind::Timeout)
+	}
+
+	/// Returns the [`DiagnosticCategory`] the error is reported with
+	///
+	/// returns: [`DiagnosticCategory`]
+	pub(crate) fn category(&self) -> DiagnosticCategory {
+		match &self.inner.kind {
+			Kind::Io(Io::Serialize) => DiagnosticCategory::Parse,
+			Kind::Subscription(_) => DiagnosticCategory::Handler,
+			Kind::ArangoDB(ArangoDBError::ServerMismatch)
+			| Kind::ArangoDB(ArangoDBError::StartupDrift)
+			| Kind::ArangoDB(ArangoDBError::TickExpired) => DiagnosticCategory::Checkpoint,
+			_ => DiagnosticCategory::Transport,
+		}
+	}
+
 	/// Returns error description based on its type
 	///
 	/// returns: [`String`]
 	fn description(&self) -> String {
 		match self.inner.kind {
 			Kind::Http => "HTTP client error".to_string(),
+			Kind::Timeout => "Timed out waiting for the ArangoDB server".to_string(),
 			Kind::ArangoDB(ArangoDBError::HttpError(s)) => {
 				format!("ArangoDB HTTP API error: {}", s.as_str())
 			}
+			Kind::ArangoDB(ArangoDBError::ReplicationUnavailable) => {
+				"ArangoDB replication logger is not available, make sure the Trigger connects to a \
+				 single server or DB-Server with replication enabled"
+					.to_string()
+			}
+			Kind::ArangoDB(ArangoDBError::ServerMismatch) => {
+				"Resume token belongs to another ArangoDB server".to_string()
+			}
+			Kind::ArangoDB(ArangoDBError::StartupDrift) => {
+				"ArangoDB server log advanced too much between init() and the first listen()"
+					.to_string()
+			}
+			Kind::ArangoDB(ArangoDBError::DatabaseNotFound) => {
+				"ArangoDB database not found".to_string()
+			}
+			Kind::ArangoDB(ArangoDBError::UnsupportedServer) => {
+				"ArangoDB server version is not supported".to_string()
+			}
+			Kind::ArangoDB(ArangoDBError::TickExpired) => {
+				"ArangoDB server log no longer holds the requested tick".to_string()
+			}
 			Kind::Io(Io::Serialize) => "Error while serializing/deserializing data".to_string(),
 			Kind::Io(Io::Other) => "I/O Error".to_string(),
+			#[cfg(feature = "tls")]
+			Kind::Tls => "TLS configuration error".to_string(),
+			Kind::Subscription(SubscriptionError::Duplicate) => {
+				"Handler is already subscribed to this event".to_string()
+			}
+			Kind::Subscription(SubscriptionError::ContextMismatch) => {
+				"Context type doesn't match the handler context type".to_string()
+			}
 		}
 	}
 }
@@ -155,6 +265,8 @@ macro_rules! err_from {
 err_from!(+ hyper::http::uri::InvalidUri, Kind::Http);
 err_from!(+ hyper::http::Error, Kind::Http);
 err_from!(+ hyper::Error, Kind::Http);
+err_from!(+ hyper::header::InvalidHeaderName, Kind::Http);
+err_from!(+ hyper::header::InvalidHeaderValue, Kind::Http);
 err_from!(+ std::io::Error, Kind::Io(Io::Other));
 err_from!(+ serde_json::Error, Kind::Io(Io::Serialize));
 err_from!(+ hyper::header::ToStrError, Kind::Io(Io::Serialize));
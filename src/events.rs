@@ -1,12 +1,12 @@
 use std::any::Any;
-use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::api::DocumentOperation;
 
 /// Defines the type of event the handler will be listening to
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum HandlerEvent {
 	/// Insert or replace document. Triggered by
 	/// [`InsertOrReplaceDocument`](`crate::api::LogType::InsertOrReplaceDocument`) event
@@ -14,6 +14,112 @@ pub enum HandlerEvent {
 	/// Insert or replace document. Triggered by
 	/// [`RemoveDocument`](`crate::api::LogType::RemoveDocument`) event
 	Remove,
+	/// All the documents of a collection are gone, so any state derived from them should be
+	/// cleared. Triggered by [`DropCollection`](`crate::api::LogType::DropCollection`) and
+	/// [`TruncateCollection`](`crate::api::LogType::TruncateCollection`) events. Note that
+	/// collection subscriptions are bound to the collection name, so they keep working if the
+	/// collection gets recreated afterwards
+	CollectionReset,
+}
+
+/// Defines what happens when the same [`Handler`] gets subscribed twice to the same
+/// [`HandlerEvent`] with the same scope, i.e. globally or to the same collection
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DuplicatePolicy {
+	/// Keep both subscriptions, so the handler gets dispatched twice, reporting a warning to the
+	/// `Observer`, see [`Trigger::on_diagnostic`](`crate::Trigger::on_diagnostic`)
+	#[default]
+	Allow,
+	/// Keep the existing subscription and ignore the new one
+	Ignore,
+	/// Replace the existing subscription (and its context) with the new one
+	Replace,
+	/// Reject the new subscription returning an error
+	Error,
+}
+
+/// Defines how the document operations received on each [`Trigger::listen`] call get dispatched
+///
+/// [`Trigger::listen`]: crate::Trigger::listen
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DispatchMode {
+	/// Operations are dispatched one after the other in log order
+	#[default]
+	Serial,
+	/// Operations of different collections are dispatched concurrently while operations on the
+	/// same collection keep their log order. The whole batch is buffered before being dispatched
+	/// and handler futures are polled concurrently on the listening task
+	PerCollection,
+}
+
+/// Report of a single event dispatch, listing what happened with every [`Handler`] subscribed
+/// to it. Useful to find out why a handler didn't get called
+#[derive(Clone, Debug)]
+pub struct DispatchReport {
+	/// The dispatched [`HandlerEvent`]
+	pub event: HandlerEvent,
+	/// The collection of the dispatched document operation
+	pub collection: String,
+	/// The key of the dispatched document, if present, see
+	/// [`Trigger::with_key_path`](`crate::Trigger::with_key_path`)
+	pub key: Option<String>,
+	/// The dispatch of each [`Handler`] subscribed to the event, in dispatch order
+	pub handlers: Vec<HandlerDispatch>,
+}
+
+/// Dispatch of an event to a single [`Handler`]
+#[derive(Clone, Debug)]
+pub struct HandlerDispatch {
+	/// The handler type name, as returned by [`std::any::type_name`]
+	pub handler: &'static str,
+	/// Whether the handler is subscribed to the event collection (`true`) or to all collections
+	/// (`false`)
+	pub scoped: bool,
+	/// What happened with the handler
+	pub outcome: DispatchOutcome,
+	/// Time spent awaiting the handler, zero if it was skipped
+	pub duration: Duration,
+}
+
+/// Outcome of dispatching an event to a [`Handler`]. As [`Handler::call`] can't fail, a handler
+/// is either called or skipped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DispatchOutcome {
+	/// The handler was called
+	Called,
+	/// The handler was not called
+	Skipped(SkipReason),
+}
+
+/// Reason why a [`Handler`] was not called
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+	/// The [`Handler::dispatch`] of the subscription returned [`None`], which the provided method
+	/// only does when the [`HandlerContext`] can't be downcast to the [`Handler::Context`] type
+	ContextMismatch,
+	/// The document deadline had already passed when the event got dispatched, see
+	/// [`StalePolicy::Skip`]
+	Expired,
+	/// The [`Trigger`](`crate::Trigger`) is in dry-run mode, so the handler would have been
+	/// called otherwise
+	DryRun,
+}
+
+/// Defines what happens with document operations whose deadline has already passed by the time
+/// they get dispatched, e.g. when catching up after an outage. See [`Trigger::with_deadline`]
+///
+/// [`Trigger::with_deadline`]: crate::Trigger::with_deadline
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum StalePolicy {
+	/// Stale operations are not dispatched to handlers, which get reported as
+	/// [`SkipReason::Expired`]
+	#[default]
+	Skip,
+	/// Stale operations are dispatched after all the other operations of the same
+	/// [`Trigger::listen`] batch. The whole batch is buffered before being dispatched
+	///
+	/// [`Trigger::listen`]: crate::Trigger::listen
+	Deprioritize,
 }
 
 /// Handler context wrapper and extractor.
@@ -62,7 +168,7 @@ impl<T: ?Sized> Clone for HandlerContext<T> {
 /// [`call`]: Handler::call
 ///
 /// # Examples
-/// ```
+/// ```no_run
 /// use arangodb_events_rs::api::DocumentOperation;
 /// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
 ///
@@ -80,30 +186,36 @@ impl<T: ?Sized> Clone for HandlerContext<T> {
 ///     }
 /// }
 ///
+/// # async fn example() {
 /// let mut trigger = Trigger::new(
-/// 	"http://localhost:8529/",
-/// 	"alchemy",
+///     "http://localhost:8529/",
+///     "alchemy",
 /// );
 ///
-///	trigger.subscribe::<ExampleHandler>(
-/// 	HandlerEvent::InsertOrReplace,
-/// 	HandlerContextFactory::from(MyContext {
+/// trigger.subscribe::<ExampleHandler>(
+///     HandlerEvent::InsertOrReplace,
+///     HandlerContextFactory::from(MyContext {
 ///         data: 10,
-///  	})
-/// );
+///     })
+/// ).unwrap();
 ///
-///	trigger
-///		.init()
-///		.await
-///		.expect("Error initializing ArangoDB Trigger");
+/// trigger
+///     .init()
+///     .await
+///     .expect("Error initializing ArangoDB Trigger");
 ///
-///  loop {
-///		trigger.listen().await.unwrap();
-///  }
+/// loop {
+///     trigger.listen().await.unwrap();
+/// }
+/// # }
 /// ```
 pub trait Handler: 'static {
 	type Context;
 
+	/// The [`HandlerEvent`]s the Handler is subscribed to by
+	/// [`Trigger::subscribe_auto`](`crate::Trigger::subscribe_auto`). Empty by default
+	const EVENTS: &'static [HandlerEvent] = &[];
+
 	#[cfg(feature = "async")]
 	/// Method called when the [`HandlerEvent`] the Handler is subscribed to gets dispatched from
 	/// the application [`Trigger`]
@@ -122,7 +234,7 @@ pub trait Handler: 'static {
 	/// Note: with `async` feature enabled, this method returns [`Option<AsyncHandlerOutput>`] so we
 	/// encapsulate the [`Handler::call`] into a pinned box.
 	fn dispatch<'a>(
-		ctx: &'a HandlerContext<dyn Any>,
+		ctx: &'a HandlerContext<dyn Any + Send + Sync>,
 		doc: &'a DocumentOperation,
 	) -> Option<AsyncHandlerOutput<'a>> {
 		if let Some(c) = ctx.downcast_ref::<Self::Context>() {
@@ -138,212 +250,218 @@ pub trait Handler: 'static {
 }
 
 /// Type alias for [`Handler::call`] method output
-pub type AsyncHandlerOutput<'a> = std::pin::Pin<Box<dyn std::future::Future<Output = ()> + 'a>>;
+pub type AsyncHandlerOutput<'a> = AsyncHandlerResult<'a, ()>;
 
-/// Factory to create HandlerContext.
+/// Type alias for [`OutputHandler::call`] method output
+pub type AsyncHandlerResult<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + 'a>>;
+
+/// Event handler returning a value, which the [`Trigger`](`crate::Trigger`) forwards as a
+/// [`HandlerResult`] to its results callback, see
+/// [`Trigger::on_result`](`crate::Trigger::on_result`). Useful to compute something per event,
+/// e.g. a projection, and process the results elsewhere, e.g. bulk-writing them, with no shared
+/// mutable state in the context. Results are dropped if no callback is set.
 ///
-/// Note that this is the way to create `HandlerContext` instead of using its `new` method because
-/// this helps with automatic typings as this already returns `HandlerContext<dyn Any>` instead of
-/// `HandlerContext<T>` which then can lead to some typing problems while subscribing to the
-/// [`Trigger`]. That is why [`HandlerContext::new`] is not exposed publicly
-pub struct HandlerContextFactory;
+/// It gets subscribed with [`Trigger::subscribe_output`](`crate::Trigger::subscribe_output`) or
+/// [`Trigger::subscribe_output_to`](`crate::Trigger::subscribe_output_to`) and is dispatched like
+/// a [`Handler`].
+///
+/// # Examples
+/// ```no_run
+/// use arangodb_events_rs::api::DocumentOperation;
+/// use arangodb_events_rs::{HandlerContextFactory, HandlerEvent, OutputHandler, Trigger};
+///
+/// pub struct BalanceProjection;
+///
+/// impl OutputHandler for BalanceProjection {
+///     type Context = ();
+///     type Output = (String, f64);
+///
+///     fn call(_: &(), doc: &DocumentOperation) -> (String, f64) {
+///         let balance = doc.field("balance").and_then(|v| v.as_f64()).unwrap_or_default();
+///
+///         (doc.collection.clone(), balance)
+///     }
+/// }
+///
+/// # async fn example() {
+/// let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+///
+/// let mut trigger = Trigger::new(
+///     "http://localhost:8529/",
+///     "alchemy",
+/// )
+/// .on_result(move |result| {
+///     if let Ok(row) = result.downcast::<(String, f64)>() {
+///         let _ = tx.send(row);
+///     }
+/// });
+///
+/// trigger.subscribe_output_to::<BalanceProjection>(
+///     HandlerEvent::InsertOrReplace,
+///     "accounts",
+///     HandlerContextFactory::from(()),
+/// ).unwrap();
+///
+/// trigger.init().await.unwrap();
+/// trigger.listen().await.unwrap();
+///
+/// while let Ok(row) = rx.try_recv() {
+///     println!("{:?}", row);
+/// }
+/// # }
+/// ```
+pub trait OutputHandler: 'static {
+	type Context;
 
-// This factory makes it possible to return `dyn Any` type directly, to avoid needing for
-// specifying type on the library use code
-impl HandlerContextFactory {
-	/// Creates a new `HandlerContext`
-	///
-	/// # Arguments
-	///
-	/// * `data`: The inner data to be wrapped
-	///
-	/// returns: `HandlerContext<dyn Any>`
-	///
-	/// # Examples
-	///
-	/// ```
-	/// use arangodb_events_rs::{HandlerContextFactory, Trigger, HandlerEvent};
-	///
-	/// struct MyContext(pub String);
-	///
-	/// let mut trigger = Trigger::new(
-	/// 	"http://localhost:8529/",
-	/// 	"alchemy",
-	/// );
+	/// The type of the value returned by the handler
+	type Output: Send + 'static;
+
+	#[cfg(feature = "async")]
+	/// Method called when the [`HandlerEvent`] the handler is subscribed to gets dispatched from
+	/// the application [`Trigger`](`crate::Trigger`)
 	///
-	///	trigger.subscribe::<ExistingHandler>(
-	///		HandlerEvent::InsertOrReplace,
-	///		HandlerContextFactory::from(
-	///			MyContext(String::from("This is the context data"))
-	/// 	)
-	///	);
-	/// ```
-	pub fn from<T: Any>(data: T) -> HandlerContext<dyn Any> {
-		HandlerContext::new(Box::new(data))
-	}
-}
+	/// Note: with `async` feature enabled, this method returns [`AsyncHandlerResult`]
+	fn call<'a>(
+		ctx: &'a Self::Context,
+		doc: &'a DocumentOperation,
+	) -> AsyncHandlerResult<'a, Self::Output>;
 
-/// Event subscription
-pub(crate) struct Subscription {
-	name: String,
-	callback: for<'a> fn(
-		&'a HandlerContext<dyn Any>,
-		&'a DocumentOperation,
-	) -> Option<AsyncHandlerOutput<'a>>,
-	context: HandlerContext<dyn Any>,
+	#[cfg(not(feature = "async"))]
+	/// Method called when the [`HandlerEvent`] the handler is subscribed to gets dispatched from
+	/// the application [`Trigger`](`crate::Trigger`)
+	fn call(ctx: &Self::Context, doc: &DocumentOperation) -> Self::Output;
+
+	/// Dispatch the event, downcasting the dynamic [`HandlerContext`] like [`Handler::dispatch`]
+	/// and type-erasing the returned value
+	fn dispatch<'a>(
+		ctx: &'a HandlerContext<dyn Any + Send + Sync>,
+		doc: &'a DocumentOperation,
+	) -> Option<AsyncHandlerResult<'a, Box<dyn Any + Send>>> {
+		let c = ctx.downcast_ref::<Self::Context>()?;
+
+		Some(Box::pin(async move {
+			#[cfg(feature = "async")]
+			let output = Self::call(c, doc).await;
+
+			#[cfg(not(feature = "async"))]
+			let output = Self::call(c, doc);
+
+			Box::new(output) as Box<dyn Any + Send>
+		}))
+	}
 }
 
-/// Event subscription map
-pub(crate) struct SubscriptionMap {
-	map: HashMap<HandlerEvent, Vec<Subscription>>,
+/// Value returned by an [`OutputHandler`], forwarded to
+/// [`Trigger::on_result`](`crate::Trigger::on_result`)
+pub struct HandlerResult {
+	/// The handler type name, as returned by [`std::any::type_name`]
+	pub handler: &'static str,
+	/// The dispatched [`HandlerEvent`]
+	pub event: HandlerEvent,
+	/// The collection of the dispatched document operation
+	pub collection: String,
+	value: Box<dyn Any + Send>,
 }
 
-impl SubscriptionMap {
-	/// Creates a new empty instance of `SubscriptionMap`
-	///
-	/// returns: [`SubscriptionMap`]
-	pub(crate) fn empty() -> Self {
+impl HandlerResult {
+	/// Creates a new [`HandlerResult`]
+	pub(crate) fn new(
+		handler: &'static str,
+		event: HandlerEvent,
+		collection: String,
+		value: Box<dyn Any + Send>,
+	) -> Self {
 		Self {
-			map: HashMap::new(),
+			handler,
+			event,
+			collection,
+			value,
 		}
 	}
 
-	/// Inserts into the inner map an instance of [`Subscription`] with the given handler's dispatch
-	/// method as the callback of the [`Subscription`]
-	///
-	/// # Arguments
+	/// Returns whether the value is of type `T`
 	///
-	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
-	/// * `ctx`: The [`Handler`]'s [`HandlerContext`]. Note that you could pass here any [`HandlerContext`]
-	/// with any type, but note that if its type it's not the same as the [`Handler::Context`] one the
-	/// [`Handler::call`] function is never gonna be executed as downcasting will fail.
-	pub(crate) fn insert<H: Handler>(&mut self, ev: HandlerEvent, ctx: HandlerContext<dyn Any>) {
-		let subscription = Subscription {
-			name: std::any::type_name::<H>().to_string(),
-			callback: H::dispatch,
-			context: ctx,
-		};
-
-		if let Some(v) = self.map.get_mut(&ev) {
-			v.push(subscription);
-		} else {
-			self.map.insert(ev, vec![subscription]);
-		}
+	/// returns: `bool`
+	pub fn is<T: Any>(&self) -> bool {
+		self.value.is::<T>()
 	}
 
-	/// Get all the [`Subscription`] instances attached to a [`HandlerEvent`]
-	///
-	/// # Arguments
+	/// Returns a reference to the value if it's of type `T`
 	///
-	/// * `ev`: The [`HandlerEvent`]
+	/// returns: `Option<&T>`
+	pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+		self.value.downcast_ref()
+	}
+
+	/// Takes the value if it's of type `T`, giving the [`HandlerResult`] back otherwise
 	///
-	/// returns: `Option<&Vec<Subscription, Global>>`
-	pub(crate) fn get(&self, ev: &HandlerEvent) -> Option<&Vec<Subscription>> {
-		self.map.get(ev)
+	/// returns: `Result<T, HandlerResult>`
+	pub fn downcast<T: Any>(self) -> std::result::Result<T, Self> {
+		match self.value.downcast::<T>() {
+			Ok(value) => Ok(*value),
+			Err(value) => Err(Self { value, ..self }),
+		}
 	}
 }
 
-/// Subscription manager that will hold a general [`SubscriptionMap`] for all the events triggered
-/// and one [`SubscriptionManager`] per each collection-attached [`Subscription`] indexed by the
-/// collection string
-pub(crate) struct SubscriptionManager {
-	collection_subscriptions: HashMap<String, SubscriptionMap>,
-	subscriptions: SubscriptionMap,
+impl std::fmt::Debug for HandlerResult {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("HandlerResult")
+			.field("handler", &self.handler)
+			.field("event", &self.event)
+			.field("collection", &self.collection)
+			.finish_non_exhaustive()
+	}
 }
 
-impl SubscriptionManager {
-	/// Creates a new instance of `SubscriptionManager`
-	///
-	/// returns: [`SubscriptionManager`]
-	pub(crate) fn new() -> Self {
-		Self {
-			collection_subscriptions: HashMap::new(),
-			subscriptions: SubscriptionMap::empty(),
-		}
-	}
+/// Factory to create HandlerContext.
+///
+/// Note that this is the way to create `HandlerContext` instead of using its `new` method because
+/// this helps with automatic typings as this already returns `HandlerContext<dyn Any + Send + Sync>`
+/// instead of `HandlerContext<T>` which then can lead to some typing problems while subscribing to
+/// the [`Trigger`]. That is why [`HandlerContext::new`] is not exposed publicly. Contexts must be
+/// [`Send`] and [`Sync`] as the [`SubscriptionManager`](`crate::SubscriptionManager`) holding
+/// them is shared across threads
+pub struct HandlerContextFactory;
 
-	/// Inserts into the global [`SubscriptionMap`] a [`Handler`] to subscribe to a [`HandlerEvent`]
-	/// with the given [`HandlerContext`]
+// This factory makes it possible to return `dyn Any` type directly, to avoid needing for
+// specifying type on the library use code
+impl HandlerContextFactory {
+	/// Creates a new `HandlerContext`
 	///
 	/// # Arguments
 	///
-	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
-	/// * `ctx`: The [`Handler`]'s [`HandlerContext`]. Note that you could pass here any [`HandlerContext`]
-	/// with any type, but note that if its type it's not the same as the [`Handler::Context`] one the
-	/// [`Handler::call`] function is never gonna be executed as downcasting will fail
-	pub(crate) fn insert<H: Handler>(&mut self, ev: HandlerEvent, ctx: HandlerContext<dyn Any>) {
-		self.subscriptions.insert::<H>(ev, ctx)
-	}
-
-	/// Inserts (or creates if doesn't exist) a [`Handler`] into the [`SubscriptionMap`] attached to
-	/// a collection to subscribe to a [`HandlerEvent`] with the given [`HandlerContext`]
+	/// * `data`: The inner data to be wrapped
 	///
-	/// # Arguments
+	/// returns: `HandlerContext<dyn Any + Send + Sync>`
 	///
-	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen for
-	/// * `collection`: The collection name the [`Handler`] is gonna listen for
-	/// * `ctx`: The [`Handler`]'s [`HandlerContext`]. Note that you could pass here any [`HandlerContext`]
-	/// with any type, but note that if its type it's not the same as the [`Handler::Context`] one the
-	/// [`Handler::call`] function is never gonna be executed as downcasting will fail
-	pub(crate) fn insert_to<H: Handler>(
-		&mut self,
-		ev: HandlerEvent,
-		collection: &str,
-		ctx: HandlerContext<dyn Any>,
-	) {
-		if let Some(subs) = self.collection_subscriptions.get_mut(collection) {
-			subs.insert::<H>(ev, ctx)
-		} else {
-			let mut map = SubscriptionMap::empty();
-			let ctx = map.insert::<H>(ev, ctx);
-
-			self.collection_subscriptions
-				.insert(collection.to_string(), map);
-
-			ctx
-		}
-	}
-
-	/// Triggers all the [`Subscription`]s for a [`HandlerEvent`] with the possibility of also
-	/// triggering all the [`Subscription`]s for the same [`HandlerEvent`] and a specific
-	/// collection. It will also give to the [`Subscription`] callback the [`DocumentOperation`]
-	/// data of the event
+	/// # Examples
 	///
-	/// # Arguments
+	/// ```
+	/// use arangodb_events_rs::{HandlerContextFactory, Trigger, HandlerEvent};
+	/// # use arangodb_events_rs::Handler;
+	/// # use arangodb_events_rs::api::DocumentOperation;
 	///
-	/// * `ev`: The [`HandlerEvent`] to be triggered
-	/// * `doc`: The [`DocumentOperation`] data
-	/// * `collection`: [`Some`] to trigger collection-attached [`Subscription`]  callbacks or
-	/// [`None`] to trigger only global [`Subscription`] callbacks
-	pub(crate) async fn call(
-		&self,
-		ev: HandlerEvent,
-		doc: &DocumentOperation,
-		collection: Option<&str>,
-	) {
-		async fn dispatch_event(e: &HandlerEvent, map: &SubscriptionMap, doc: &DocumentOperation) {
-			if let Some(subs) = map.get(e) {
-				for sub in subs {
-					if let Some(cb) = (sub.callback)(&sub.context, doc) {
-						cb.await
-					} else {
-						println!(
-							"arangodb_events_rs: warn: unable to downcast context for {:?} handler",
-							sub.name
-						)
-					}
-				}
-			}
-		}
-
-		// Call generic subscriptions with no collection attached
-		dispatch_event(&ev, &self.subscriptions, doc).await;
-
-		// Call subscriptions for specific collection if matches
-		if let Some(col) = collection {
-			if let Some(map) = self.collection_subscriptions.get(col) {
-				dispatch_event(&ev, map, doc).await;
-			}
-		}
+	/// struct MyContext(pub String);
+	/// # struct ExistingHandler;
+	/// # impl Handler for ExistingHandler {
+	/// #     type Context = MyContext;
+	/// #     fn call(_: &MyContext, _: &DocumentOperation) {}
+	/// # }
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger.subscribe::<ExistingHandler>(
+	///     HandlerEvent::InsertOrReplace,
+	///     HandlerContextFactory::from(
+	///         MyContext(String::from("This is the context data"))
+	///     )
+	/// ).unwrap();
+	/// ```
+	pub fn from<T: Any + Send + Sync>(data: T) -> HandlerContext<dyn Any + Send + Sync> {
+		HandlerContext::new(Box::new(data))
 	}
 }
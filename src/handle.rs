@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crate::Result;
+
+/// Lightweight control handle of a [`Trigger`](`crate::Trigger`), obtained with
+/// [`Trigger::handle`](`crate::Trigger::handle`). Put it on a [`Handler`](`crate::Handler`)
+/// context to let the handler control the Trigger in response to the data it sees.
+///
+/// `TriggerHandle` is cheap to clone; internally, it uses an `Arc`.
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::api::DocumentOperation;
+/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger, TriggerHandle};
+///
+/// pub struct MaintenanceHandler;
+///
+/// impl Handler for MaintenanceHandler {
+///     type Context = TriggerHandle;
+///
+///     fn call(trigger: &TriggerHandle, doc: &DocumentOperation) {
+///         if doc.field("maintenance").and_then(|v| v.as_bool()) == Some(true) {
+///             trigger.pause();
+///         }
+///     }
+/// }
+///
+/// let mut trigger = Trigger::new(
+///     "http://localhost:8529/",
+///     "alchemy",
+/// );
+///
+/// let handle = trigger.handle();
+///
+/// trigger.subscribe_to::<MaintenanceHandler>(
+///     HandlerEvent::InsertOrReplace,
+///     "settings",
+///     HandlerContextFactory::from(handle),
+/// ).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TriggerHandle {
+	paused: Arc<AtomicBool>,
+	stopped: Arc<AtomicBool>,
+}
+
+impl TriggerHandle {
+	/// Pauses the Trigger. While paused, `listen()` calls don't poll the server and return after
+	/// a short wait, keeping the current tick
+	pub fn pause(&self) {
+		self.paused.store(true, Ordering::SeqCst);
+	}
+
+	/// Resumes a paused Trigger
+	pub fn resume(&self) {
+		self.paused.store(false, Ordering::SeqCst);
+	}
+
+	/// Returns whether the Trigger is paused
+	///
+	/// returns: `bool`
+	pub fn is_paused(&self) -> bool {
+		self.paused.load(Ordering::SeqCst)
+	}
+
+	/// Asks the Trigger to stop. Loops driving the Trigger, like
+	/// [`Trigger::run_on_dedicated_thread`](`crate::Trigger::run_on_dedicated_thread`), end after
+	/// the current `listen()` call
+	pub fn stop(&self) {
+		self.stopped.store(true, Ordering::SeqCst);
+	}
+
+	/// Returns whether the Trigger was asked to stop
+	///
+	/// returns: `bool`
+	pub fn is_stopped(&self) -> bool {
+		self.stopped.load(Ordering::SeqCst)
+	}
+}
+
+/// A [`Trigger`](`crate::Trigger`) running on its own OS thread, returned by
+/// [`Trigger::run_on_dedicated_thread`](`crate::Trigger::run_on_dedicated_thread`)
+pub struct TriggerThread {
+	handle: TriggerHandle,
+	thread: JoinHandle<Result<()>>,
+}
+
+impl TriggerThread {
+	/// Creates a new [`TriggerThread`]
+	pub(crate) fn new(handle: TriggerHandle, thread: JoinHandle<Result<()>>) -> Self {
+		Self { handle, thread }
+	}
+
+	/// Returns the [`TriggerHandle`] of the running Trigger
+	///
+	/// returns: `&TriggerHandle`
+	pub fn handle(&self) -> &TriggerHandle {
+		&self.handle
+	}
+
+	/// Asks the Trigger to stop and waits for its thread to finish
+	///
+	/// returns: `Result<()>`, the first error returned by the Trigger, if any
+	pub fn stop(self) -> Result<()> {
+		self.handle.stop();
+		self.join()
+	}
+
+	/// Waits for the Trigger thread to finish, which only happens once it's stopped or fails
+	///
+	/// returns: `Result<()>`, the first error returned by the Trigger, if any
+	pub fn join(self) -> Result<()> {
+		match self.thread.join() {
+			Ok(result) => result,
+			Err(panic) => std::panic::resume_unwind(panic),
+		}
+	}
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+/// Health of a [`Trigger`](`crate::Trigger`) regarding how far behind the server log it is
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TriggerHealth {
+	/// The Trigger lag is under the configured [`LagThreshold`]
+	Healthy,
+	/// The Trigger lag went beyond the configured [`LagThreshold`]
+	Degraded,
+}
+
+/// How far behind the server log a [`Trigger`](`crate::Trigger`) is, measured after each
+/// `listen()` call
+#[derive(Clone, Debug, Default)]
+pub struct TriggerLag {
+	/// Difference between the server last tick and the last tick processed. Note that ticks are
+	/// shared by all the databases of a server, so this is an upper bound of the real lag
+	pub ticks: u64,
+	/// Time elapsed since the Trigger stopped being caught up with the server log
+	pub behind_for: Duration,
+}
+
+/// Lag beyond which a [`Trigger`](`crate::Trigger`) flips its health to
+/// [`TriggerHealth::Degraded`] and calls its lag alert callback
+#[derive(Clone, Copy, Debug)]
+pub enum LagThreshold {
+	/// Maximum [`TriggerLag::ticks`]
+	Ticks(u64),
+	/// Maximum [`TriggerLag::behind_for`]
+	Time(Duration),
+}
+
+impl LagThreshold {
+	/// Checks whether the given [`TriggerLag`] goes beyond the threshold
+	///
+	/// # Arguments
+	///
+	/// * `lag`: The [`TriggerLag`] to check
+	///
+	/// returns: `bool`
+	pub fn is_exceeded_by(&self, lag: &TriggerLag) -> bool {
+		match self {
+			Self::Ticks(ticks) => lag.ticks > *ticks,
+			Self::Time(time) => lag.behind_for > *time,
+		}
+	}
+}
+
+/// Progress of a [`Trigger`](`crate::Trigger`) since it was created, reported periodically to
+/// its progress callback
+#[derive(Clone, Debug, Default)]
+pub struct TriggerProgress {
+	/// Number of events dispatched, including DDL operations
+	pub events: u64,
+	/// Last log tick fetched from the server
+	pub tick: u64,
+}
+
+/// Progress of a [`Trigger`](`crate::Trigger`) catching up with the server log, reported
+/// periodically to its catch-up callback while it's behind. Estimates are derived from tick
+/// arithmetic, so they get more accurate as the catch-up goes on
+#[derive(Clone, Debug, Default)]
+pub struct CatchUpProgress {
+	/// Number of events dispatched since the Trigger fell behind
+	pub processed: u64,
+	/// Estimated number of events left until catching up, from the density of events per tick
+	/// observed so far, or the server `totalEvents` before any event got dispatched
+	pub remaining_estimate: u64,
+	/// Estimated time left until catching up, from an exponentially weighted average of the tick
+	/// advance rate. [`None`] until the rate can be measured
+	pub eta: Option<Duration>,
+}
+
+/// Measurements of a single `listen()` call, useful to tune the polling of a
+/// [`Trigger`](`crate::Trigger`) based on the observed server behavior
+#[derive(Clone, Debug, Default)]
+pub struct PollStats {
+	/// Time elapsed until the server response headers were received, including retries
+	pub response_time: Duration,
+	/// Time elapsed until the whole response was read and its events dispatched
+	pub duration: Duration,
+	/// Size of the response body read, in bytes
+	pub body_size: u64,
+	/// Number of events dispatched
+	pub events: usize,
+}
+
+/// Check of how far the server log advanced between [`Trigger::init`] and the first
+/// [`Trigger::listen`] call, see [`Trigger::with_startup_probe`]
+///
+/// [`Trigger::init`]: crate::Trigger::init
+/// [`Trigger::listen`]: crate::Trigger::listen
+/// [`Trigger::with_startup_probe`]: crate::Trigger::with_startup_probe
+#[derive(Clone, Copy, Debug)]
+pub enum StartupProbe {
+	/// Print a warning if the log advanced more than `max_ticks`
+	Warn { max_ticks: u64 },
+	/// Fail the first `listen()` call if the log advanced more than `max_ticks`
+	Error { max_ticks: u64 },
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+/// Connection pool settings of the [`Trigger`](`crate::Trigger`) HTTP client, to tune the
+/// polling client for the network environment. Unset settings keep the `hyper` defaults
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{Trigger, TriggerHttpConfig};
+/// use std::time::Duration;
+///
+/// let mut trigger = Trigger::new(
+///     "http://localhost:8529/",
+///     "alchemy",
+/// )
+/// .with_http_config(
+///     TriggerHttpConfig::new()
+///         .with_pool_idle_timeout(Duration::from_secs(30))
+///         .with_pool_max_idle_per_host(2)
+///         .with_keepalive(Duration::from_secs(60)),
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TriggerHttpConfig {
+	pub(crate) pool_idle_timeout: Option<Duration>,
+	pub(crate) pool_max_idle_per_host: Option<usize>,
+	pub(crate) keepalive: Option<Duration>,
+}
+
+impl TriggerHttpConfig {
+	/// Creates a new default [`TriggerHttpConfig`]
+	///
+	/// returns: [`TriggerHttpConfig`]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets how long idle connections are kept in the pool before being closed, 90 seconds by
+	/// default
+	///
+	/// # Arguments
+	///
+	/// * `timeout`: The idle timeout
+	///
+	/// returns: [`TriggerHttpConfig`]
+	pub fn with_pool_idle_timeout(mut self, timeout: Duration) -> Self {
+		self.pool_idle_timeout = Some(timeout);
+		self
+	}
+
+	/// Sets the maximum number of idle connections kept in the pool per host, unlimited by
+	/// default. Set it to `0` to disable connection reuse
+	///
+	/// # Arguments
+	///
+	/// * `max`: The maximum number of idle connections
+	///
+	/// returns: [`TriggerHttpConfig`]
+	pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+		self.pool_max_idle_per_host = Some(max);
+		self
+	}
+
+	/// Enables TCP keep-alive probes on the connections, sent after they've been idle for the
+	/// given interval, so dead connections are detected behind NATs and load balancers
+	///
+	/// # Arguments
+	///
+	/// * `interval`: The idle time before keep-alive probes are sent
+	///
+	/// returns: [`TriggerHttpConfig`]
+	pub fn with_keepalive(mut self, interval: Duration) -> Self {
+		self.keepalive = Some(interval);
+		self
+	}
+}
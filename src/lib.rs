@@ -3,11 +3,43 @@ extern crate base64;
 pub(crate) mod utils;
 
 pub mod api;
+pub mod config;
 pub mod deserialize;
 pub mod errors;
 pub mod events;
+pub mod handle;
+pub mod health;
+pub mod http;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod observer;
+pub mod proxy;
+pub mod resume;
+pub mod retry;
+pub mod scheduler;
+pub mod server;
+pub mod stats;
+pub mod subscriptions;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod trigger;
+pub mod users;
 
+pub use config::*;
 pub use errors::*;
 pub use events::*;
+pub use handle::*;
+pub use health::*;
+pub use http::*;
+pub use observer::*;
+pub use proxy::*;
+pub use resume::*;
+pub use retry::*;
+pub use scheduler::*;
+pub use server::*;
+pub use stats::*;
+pub use subscriptions::*;
+#[cfg(feature = "tls")]
+pub use tls::*;
 pub use trigger::*;
+pub use users::*;
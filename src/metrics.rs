@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+#[cfg(feature = "metrics-server")]
+use std::fmt::Write;
+#[cfg(feature = "metrics-server")]
+use std::net::SocketAddr;
+#[cfg(feature = "metrics-server")]
+use std::result::Result as StdResult;
+#[cfg(feature = "metrics-server")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[cfg(feature = "metrics-server")]
+use hyper::service::{make_service_fn, service_fn};
+#[cfg(feature = "metrics-server")]
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+#[cfg(feature = "metrics-server")]
+use crate::Result;
+use crate::{HandlerEvent, TriggerHealth, TriggerLag};
+
+/// Labels attached to every per-invocation handler metric so the same [`Handler`] subscribed to
+/// several collections or events is reported separately
+///
+/// [`Handler`]: crate::Handler
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HandlerMetricLabels {
+	/// The handler type name, as returned by [`std::any::type_name`]
+	pub handler: String,
+	/// The collection of the document operation that got dispatched
+	pub collection: String,
+	/// The dispatched [`HandlerEvent`]
+	pub event: HandlerEvent,
+}
+
+/// Aggregated metrics for a single [`HandlerMetricLabels`] set
+#[derive(Clone, Debug, Default)]
+pub struct HandlerMetrics {
+	/// Number of times the handler has been invoked
+	pub count: u64,
+	/// Total time spent awaiting the handler
+	pub duration: Duration,
+}
+
+/// Registry holding all the [`HandlerMetrics`] indexed by their [`HandlerMetricLabels`] along
+/// with the last [`TriggerHealth`] and [`TriggerLag`] of the [`Trigger`](`crate::Trigger`)
+pub(crate) struct MetricsRegistry {
+	handlers: Mutex<HashMap<HandlerMetricLabels, HandlerMetrics>>,
+	health: Mutex<(TriggerHealth, TriggerLag)>,
+}
+
+impl MetricsRegistry {
+	/// Creates a new empty instance of `MetricsRegistry`
+	///
+	/// returns: [`MetricsRegistry`]
+	pub(crate) fn new() -> Self {
+		Self {
+			handlers: Mutex::new(HashMap::new()),
+			health: Mutex::new((TriggerHealth::Healthy, TriggerLag::default())),
+		}
+	}
+
+	/// Records one handler invocation
+	///
+	/// # Arguments
+	///
+	/// * `labels`: The [`HandlerMetricLabels`] identifying the invocation
+	/// * `duration`: The time spent awaiting the handler
+	pub(crate) fn record(&self, labels: HandlerMetricLabels, duration: Duration) {
+		let mut handlers = self.handlers.lock().unwrap();
+		let metrics = handlers.entry(labels).or_default();
+
+		metrics.count += 1;
+		metrics.duration += duration;
+	}
+
+	/// Returns a snapshot of all the recorded [`HandlerMetrics`]
+	///
+	/// returns: `HashMap<HandlerMetricLabels, HandlerMetrics>`
+	pub(crate) fn snapshot(&self) -> HashMap<HandlerMetricLabels, HandlerMetrics> {
+		self.handlers.lock().unwrap().clone()
+	}
+
+	/// Records the [`TriggerHealth`] and [`TriggerLag`] measured on the last `listen()` call
+	///
+	/// # Arguments
+	///
+	/// * `health`: The current [`TriggerHealth`]
+	/// * `lag`: The current [`TriggerLag`]
+	pub(crate) fn record_health(&self, health: TriggerHealth, lag: TriggerLag) {
+		*self.health.lock().unwrap() = (health, lag);
+	}
+
+	/// Renders all the recorded metrics in the Prometheus text exposition format
+	///
+	/// returns: [`String`]
+	#[cfg(feature = "metrics-server")]
+	fn render_prometheus(&self) -> String {
+		let handlers = self.snapshot();
+		let (health, lag) = self.health.lock().unwrap().clone();
+		let mut out = String::new();
+
+		write_counter(
+			&mut out,
+			"arangodb_events_handler_calls_total",
+			&handlers,
+			|m| m.count as f64,
+		);
+		write_counter(
+			&mut out,
+			"arangodb_events_handler_duration_seconds_total",
+			&handlers,
+			|m| m.duration.as_secs_f64(),
+		);
+
+		let _ = writeln!(out, "# TYPE arangodb_events_lag_ticks gauge");
+		let _ = writeln!(out, "arangodb_events_lag_ticks {}", lag.ticks);
+		let _ = writeln!(out, "# TYPE arangodb_events_lag_seconds gauge");
+		let _ = writeln!(
+			out,
+			"arangodb_events_lag_seconds {}",
+			lag.behind_for.as_secs_f64()
+		);
+		let _ = writeln!(out, "# TYPE arangodb_events_healthy gauge");
+		let _ = writeln!(
+			out,
+			"arangodb_events_healthy {}",
+			(health == TriggerHealth::Healthy) as u8
+		);
+
+		out
+	}
+}
+
+/// Writes a Prometheus counter with one sample per [`HandlerMetricLabels`] set
+#[cfg(feature = "metrics-server")]
+fn write_counter<F: Fn(&HandlerMetrics) -> f64>(
+	out: &mut String,
+	name: &str,
+	handlers: &HashMap<HandlerMetricLabels, HandlerMetrics>,
+	value: F,
+) {
+	let _ = writeln!(out, "# TYPE {} counter", name);
+
+	for (labels, metrics) in handlers {
+		let _ = writeln!(
+			out,
+			"{}{{handler=\"{}\",collection=\"{}\",event=\"{:?}\"}} {}",
+			name,
+			escape_label(&labels.handler),
+			escape_label(&labels.collection),
+			labels.event,
+			value(metrics)
+		);
+	}
+}
+
+/// Escapes a Prometheus label value
+#[cfg(feature = "metrics-server")]
+fn escape_label(value: &str) -> String {
+	value
+		.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n")
+}
+
+/// Runs an HTTP server on the given address exposing the [`MetricsRegistry`] on `GET /metrics`
+/// in the Prometheus text format and the [`TriggerHealth`] on `GET /health`, which responds
+/// `503 Service Unavailable` while degraded
+///
+/// # Arguments
+///
+/// * `registry`: The [`MetricsRegistry`] to expose
+/// * `addr`: The address to listen on
+///
+/// returns: `Result<()>`
+#[cfg(feature = "metrics-server")]
+pub(crate) async fn serve(registry: Arc<MetricsRegistry>, addr: SocketAddr) -> Result<()> {
+	let make_service = make_service_fn(move |_| {
+		let registry = registry.clone();
+
+		async move {
+			Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+				let registry = registry.clone();
+
+				async move { respond(&registry, req) }
+			}))
+		}
+	});
+
+	Server::try_bind(&addr)?.serve(make_service).await?;
+
+	Ok(())
+}
+
+/// Builds the response of the metrics HTTP server for a single request
+#[cfg(feature = "metrics-server")]
+fn respond(
+	registry: &MetricsRegistry,
+	req: Request<Body>,
+) -> StdResult<Response<Body>, hyper::http::Error> {
+	match (req.method(), req.uri().path()) {
+		(&Method::GET, "/metrics") => Response::builder()
+			.header(
+				hyper::header::CONTENT_TYPE,
+				"text/plain; version=0.0.4; charset=utf-8",
+			)
+			.body(Body::from(registry.render_prometheus())),
+		(&Method::GET, "/health") => {
+			let (health, _) = *registry.health.lock().unwrap();
+
+			let (status, body) = match health {
+				TriggerHealth::Healthy => (StatusCode::OK, "healthy"),
+				TriggerHealth::Degraded => (StatusCode::SERVICE_UNAVAILABLE, "degraded"),
+			};
+
+			Response::builder().status(status).body(Body::from(body))
+		}
+		_ => Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(Body::empty()),
+	}
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// Severity of a [`Diagnostic`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Severity {
+	/// Something unexpected happened but the [`Trigger`](`crate::Trigger`) recovered from it,
+	/// like a request that is gonna be retried
+	Warning,
+	/// An operation failed, like a `listen()` call returning an error or a handler that couldn't
+	/// be called
+	Error,
+}
+
+/// Area of the [`Trigger`](`crate::Trigger`) a [`Diagnostic`] comes from
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DiagnosticCategory {
+	/// Communication with the ArangoDB server: connection, TLS and HTTP errors
+	Transport,
+	/// Server responses that couldn't be read or deserialized
+	Parse,
+	/// Handler subscriptions and dispatches
+	Handler,
+	/// Log tick bookkeeping: resume tokens and startup probes
+	Checkpoint,
+}
+
+/// Warning or error reported by a [`Trigger`](`crate::Trigger`) to its diagnostic callback, see
+/// [`Trigger::on_diagnostic`](`crate::Trigger::on_diagnostic`)
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+	/// The [`Severity`] of the diagnostic
+	pub severity: Severity,
+	/// The [`DiagnosticCategory`] of the diagnostic
+	pub category: DiagnosticCategory,
+	/// Human readable description
+	pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let severity = match self.severity {
+			Severity::Warning => "warn",
+			Severity::Error => "error",
+		};
+
+		write!(f, "{}: {:?}: {}", severity, self.category, self.message)
+	}
+}
+
+/// Callback invoked with each [`Diagnostic`]
+type DiagnosticCallback = Box<dyn Fn(&Diagnostic) + Send + Sync>;
+
+/// Routes the [`Diagnostic`]s of a [`Trigger`](`crate::Trigger`) to its diagnostic callback, or
+/// prints them to the standard output if there's none
+#[derive(Clone, Default)]
+pub(crate) struct Observer {
+	callback: Option<Arc<DiagnosticCallback>>,
+}
+
+impl Observer {
+	/// Sets the diagnostic callback
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The callback invoked with each [`Diagnostic`]
+	pub(crate) fn set<F: Fn(&Diagnostic) + Send + Sync + 'static>(&mut self, callback: F) {
+		self.callback = Some(Arc::new(Box::new(callback)));
+	}
+
+	/// Reports a [`Diagnostic`]
+	///
+	/// # Arguments
+	///
+	/// * `severity`: The [`Severity`] of the diagnostic
+	/// * `category`: The [`DiagnosticCategory`] of the diagnostic
+	/// * `message`: The diagnostic description
+	pub(crate) fn report(&self, severity: Severity, category: DiagnosticCategory, message: String) {
+		let diagnostic = Diagnostic {
+			severity,
+			category,
+			message,
+		};
+
+		match &self.callback {
+			Some(callback) => callback(&diagnostic),
+			None => println!("arangodb_events_rs: {}", diagnostic),
+		}
+	}
+}
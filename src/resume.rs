@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+
+/// Replication API a [`Trigger`](`crate::Trigger`) reads the server log from
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogApi {
+	/// **`GET /_api/replication/logger-follow`**, removed on ArangoDB 3.8
+	#[default]
+	LoggerFollow,
+	/// **`GET /_api/wal/tail`**, available since ArangoDB 3.3 with the RocksDB engine
+	WalTail,
+}
+
+/// Everything needed to safely resume a [`Trigger`](`crate::Trigger`) where it stopped. Store it
+/// as a single opaque value, e.g. serialized as JSON, after each `listen()` call and give it back
+/// on restart with [`Trigger::with_resume_token`](`crate::Trigger::with_resume_token`).
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{ResumeToken, Trigger};
+///
+/// let trigger = Trigger::new(
+///     "http://localhost:8529/",
+///     "alchemy",
+/// );
+///
+/// let stored = serde_json::to_string(&trigger.resume_token()).unwrap();
+///
+/// let token: ResumeToken = serde_json::from_str(&stored).unwrap();
+///
+/// let mut trigger = Trigger::new(
+///     "http://localhost:8529/",
+///     "alchemy",
+/// )
+/// .with_resume_token(token);
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeToken {
+	/// The last log tick processed
+	pub tick: String,
+	/// The id of the server the tick belongs to, as ticks are meaningless on other servers
+	#[serde(default)]
+	pub server_id: Option<String>,
+	/// The id of the replication barrier held by the Trigger, if any
+	#[serde(default)]
+	pub barrier_id: Option<String>,
+	/// The [`LogApi`] the tick comes from
+	#[serde(default)]
+	pub api: LogApi,
+	/// The transactions still open at the tick, only kept with
+	/// [`Trigger::with_transaction_checkpoint`](`crate::Trigger::with_transaction_checkpoint`)
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub transactions: Vec<OpenTransaction>,
+}
+
+/// Transaction started but not yet committed or aborted at the tick of a [`ResumeToken`]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct OpenTransaction {
+	/// The transaction id
+	pub tid: String,
+	/// The tick the transaction started at
+	pub tick: String,
+	/// The document operations of the transaction read so far, as serialized
+	/// [`Event`](`crate::api::Event`)s
+	pub events: Vec<JsonValue>,
+}
+
+/// Checkpoint persistence of a [`Trigger`](`crate::Trigger`), see
+/// [`Trigger::with_tick_store`](`crate::Trigger::with_tick_store`). The Trigger loads its position
+/// from the store on `init()` and saves it after each `listen()` call that processed new log
+/// entries, so a restarted process resumes where the previous one stopped. The position is kept
+/// as a [`ResumeToken`], as a tick alone is meaningless on other servers.
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{ResumeToken, Result, TickStore};
+/// use std::sync::Mutex;
+///
+/// #[derive(Default)]
+/// struct MemoryTickStore(Mutex<Option<ResumeToken>>);
+///
+/// impl TickStore for MemoryTickStore {
+///     fn load(&self) -> Result<Option<ResumeToken>> {
+///         Ok(self.0.lock().unwrap().clone())
+///     }
+///
+///     fn save(&self, token: &ResumeToken) -> Result<()> {
+///         *self.0.lock().unwrap() = Some(token.clone());
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait TickStore: Send + Sync {
+	/// Returns the last saved [`ResumeToken`], or [`None`] if nothing was saved yet
+	fn load(&self) -> Result<Option<ResumeToken>>;
+
+	/// Saves the [`ResumeToken`] of the last processed log entries
+	fn save(&self, token: &ResumeToken) -> Result<()>;
+}
+
+/// [`TickStore`] keeping the [`ResumeToken`] as JSON on a local file. Saves are atomic: the token
+/// is written and synced to a temporary file next to the target, which then replaces it, so a
+/// crash never leaves a truncated checkpoint behind.
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{FileTickStore, ResumeToken, TickStore, Trigger};
+///
+/// let path = std::env::temp_dir().join("arangodb-events-rs-doctest.tick");
+/// let store = FileTickStore::new(&path);
+///
+/// let token = Trigger::new("http://localhost:8529/", "alchemy").resume_token();
+///
+/// store.save(&token).unwrap();
+/// assert_eq!(store.load().unwrap(), Some(token));
+///
+/// std::fs::remove_file(&path).unwrap();
+/// assert_eq!(store.load().unwrap(), None);
+///
+/// let trigger = Trigger::new("http://localhost:8529/", "alchemy")
+///     .with_tick_store(FileTickStore::new(&path));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileTickStore {
+	path: PathBuf,
+}
+
+impl FileTickStore {
+	/// Creates a new [`FileTickStore`]. The file is created on the first save, its parent
+	/// directory must exist
+	///
+	/// # Arguments
+	///
+	/// * `path`: Path of the checkpoint file
+	///
+	/// returns: `FileTickStore`
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+
+	/// Returns the path of the checkpoint file
+	///
+	/// returns: `&Path`
+	pub fn path(&self) -> &Path {
+		self.path.as_path()
+	}
+
+	/// Returns the path of the temporary file written before replacing the checkpoint
+	fn temp_path(&self) -> PathBuf {
+		let mut path = self.path.clone().into_os_string();
+		path.push(".tmp");
+
+		PathBuf::from(path)
+	}
+}
+
+impl TickStore for FileTickStore {
+	fn load(&self) -> Result<Option<ResumeToken>> {
+		let data = match fs::read(&self.path) {
+			Ok(data) => data,
+			Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e.into()),
+		};
+
+		Ok(Some(serde_json::from_slice(&data)?))
+	}
+
+	fn save(&self, token: &ResumeToken) -> Result<()> {
+		let temp = self.temp_path();
+
+		let mut file = File::create(&temp)?;
+		file.write_all(&serde_json::to_vec(token)?)?;
+		file.sync_all()?;
+
+		fs::rename(&temp, &self.path)?;
+
+		// Persist the rename itself, directories can't be synced on every platform
+		#[cfg(unix)]
+		if let Some(dir) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+			File::open(dir)?.sync_all()?;
+		}
+
+		Ok(())
+	}
+}
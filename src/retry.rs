@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+/// Policy deciding whether and when a failed request to the ArangoDB server gets retried.
+/// Requests are only retried on transient failures: connection errors and `5xx` HTTP responses.
+///
+/// Implement this trait to plug a custom retry strategy into a [`Trigger`](`crate::Trigger`),
+/// or use the built-in [`Backoff`].
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{RetryPolicy, Trigger};
+/// use std::time::Duration;
+///
+/// struct FixedRetry;
+///
+/// impl RetryPolicy for FixedRetry {
+///     fn retry_after(&self, attempt: u32) -> Option<Duration> {
+///         (attempt < 3).then(|| Duration::from_secs(1))
+///     }
+/// }
+///
+/// let mut trigger = Trigger::new(
+///     "http://localhost:8529/",
+///     "alchemy",
+/// )
+/// .with_retry_policy(FixedRetry);
+/// ```
+pub trait RetryPolicy: Send + Sync {
+	/// Returns how long to wait before retrying a failed request, or [`None`] to give up and
+	/// return the error
+	///
+	/// # Arguments
+	///
+	/// * `attempt`: The number of retries already done for this request, starting at `0`
+	///
+	/// returns: `Option<Duration>`
+	fn retry_after(&self, attempt: u32) -> Option<Duration>;
+}
+
+/// Exponential backoff [`RetryPolicy`]. The delay starts at `initial` and gets multiplied by
+/// `multiplier` after each retry, up to `max`.
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{Backoff, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(5))
+///     .with_multiplier(3.0)
+///     .with_max_retries(5);
+///
+/// assert_eq!(backoff.retry_after(1), Some(Duration::from_millis(300)));
+/// assert_eq!(backoff.retry_after(5), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Backoff {
+	initial: Duration,
+	max: Duration,
+	multiplier: f64,
+	max_retries: Option<u32>,
+}
+
+impl Backoff {
+	/// Creates a new [`Backoff`] doubling the delay after each retry and retrying forever
+	///
+	/// # Arguments
+	///
+	/// * `initial`: The delay before the first retry
+	/// * `max`: The maximum delay between retries
+	///
+	/// returns: [`Backoff`]
+	pub fn new(initial: Duration, max: Duration) -> Self {
+		Self {
+			initial,
+			max,
+			multiplier: 2.0,
+			max_retries: None,
+		}
+	}
+
+	/// Sets the factor the delay gets multiplied by after each retry
+	///
+	/// # Arguments
+	///
+	/// * `multiplier`: The delay factor
+	///
+	/// returns: [`Backoff`]
+	pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+		self.multiplier = multiplier;
+		self
+	}
+
+	/// Sets the maximum number of retries of a single request
+	///
+	/// # Arguments
+	///
+	/// * `max_retries`: The maximum number of retries
+	///
+	/// returns: [`Backoff`]
+	pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+		self.max_retries = Some(max_retries);
+		self
+	}
+}
+
+impl Default for Backoff {
+	fn default() -> Self {
+		Self::new(Duration::from_millis(500), Duration::from_secs(30))
+	}
+}
+
+impl RetryPolicy for Backoff {
+	fn retry_after(&self, attempt: u32) -> Option<Duration> {
+		if self.max_retries.map(|max| attempt >= max).unwrap_or(false) {
+			return None;
+		}
+
+		let delay = self.initial.as_secs_f64() * self.multiplier.powi(attempt as i32);
+
+		Some(Duration::from_secs_f64(delay.min(self.max.as_secs_f64())))
+	}
+}
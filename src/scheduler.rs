@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Scheduler shared by several [`Trigger`](`crate::Trigger`)s running in the same process to
+/// bound how many of them poll the server log at the same time. Waiting Triggers are served in
+/// FIFO order, so a Trigger on a busy database releases its turn after each `listen()` call
+/// instead of starving the polls of quieter ones.
+///
+/// `PollScheduler` is cheap to clone; internally, it uses an `Arc`.
+///
+/// # Examples
+///
+/// ```
+/// use arangodb_events_rs::{PollScheduler, Trigger};
+///
+/// let scheduler = PollScheduler::new(2);
+///
+/// let tenants = ["tenant_a", "tenant_b", "tenant_c"].map(|database| {
+///     Trigger::new("http://localhost:8529/", database).with_poll_scheduler(scheduler.clone())
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct PollScheduler {
+	semaphore: Arc<Semaphore>,
+}
+
+impl PollScheduler {
+	/// Creates a new [`PollScheduler`]
+	///
+	/// # Arguments
+	///
+	/// * `concurrent_polls`: The maximum number of Triggers polling at the same time
+	///
+	/// returns: [`PollScheduler`]
+	pub fn new(concurrent_polls: usize) -> Self {
+		Self {
+			semaphore: Arc::new(Semaphore::new(concurrent_polls)),
+		}
+	}
+
+	/// Waits for a poll turn, which is released when the returned permit is dropped
+	///
+	/// returns: [`OwnedSemaphorePermit`]
+	pub(crate) async fn acquire(&self) -> OwnedSemaphorePermit {
+		self.semaphore
+			.clone()
+			.acquire_owned()
+			.await
+			.expect("PollScheduler semaphore is never closed")
+	}
+}
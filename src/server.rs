@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ArangoDBError, Error, Kind, LogApi, Result};
+
+/// Edition of an ArangoDB server
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerEdition {
+	#[default]
+	Community,
+	Enterprise,
+}
+
+/// Server capabilities a [`Trigger`](`crate::Trigger`) depends on, only available on some
+/// ArangoDB versions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ServerFeature {
+	/// Reading the server log through **`GET /_api/replication/logger-follow`**
+	LoggerFollow,
+	/// Reading the server log through **`GET /_api/wal/tail`**
+	WalTail,
+}
+
+impl ServerFeature {
+	/// Returns the first ArangoDB version supporting the feature, if limited
+	fn since(&self) -> Option<(u32, u32)> {
+		match self {
+			Self::LoggerFollow => None,
+			Self::WalTail => Some((3, 3)),
+		}
+	}
+
+	/// Returns the first ArangoDB version no longer supporting the feature, if any
+	fn until(&self) -> Option<(u32, u32)> {
+		match self {
+			Self::LoggerFollow => Some((3, 8)),
+			Self::WalTail => None,
+		}
+	}
+
+	/// Returns the feature name used on error messages
+	fn name(&self) -> &'static str {
+		match self {
+			Self::LoggerFollow => "logger-follow",
+			Self::WalTail => "wal/tail",
+		}
+	}
+}
+
+impl From<LogApi> for ServerFeature {
+	fn from(api: LogApi) -> Self {
+		match api {
+			LogApi::LoggerFollow => Self::LoggerFollow,
+			LogApi::WalTail => Self::WalTail,
+		}
+	}
+}
+
+/// Version and edition of the ArangoDB server a [`Trigger`](`crate::Trigger`) connects to, as
+/// returned by **`GET /_api/version`**
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ServerInfo {
+	/// The server version, e.g. `3.10.4`
+	pub version: String,
+	#[serde(rename = "license", default)]
+	pub edition: ServerEdition,
+}
+
+impl ServerInfo {
+	/// Returns the major and minor numbers of the server version, or [`None`] if it can't be
+	/// parsed
+	///
+	/// returns: `Option<(u32, u32)>`
+	pub fn version_number(&self) -> Option<(u32, u32)> {
+		let mut parts = self.version.split(|c: char| !c.is_ascii_digit());
+
+		let major = parts.next()?.parse().ok()?;
+		let minor = parts.next()?.parse().ok()?;
+
+		Some((major, minor))
+	}
+
+	/// Returns whether the server supports the given [`ServerFeature`]. Servers with an unknown
+	/// version are assumed to support everything
+	///
+	/// # Arguments
+	///
+	/// * `feature`: The [`ServerFeature`]
+	///
+	/// returns: `bool`
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{ServerFeature, ServerInfo};
+	///
+	/// let info: ServerInfo = serde_json::from_str(
+	///     r#"{ "server": "arango", "version": "3.11.2", "license": "community" }"#,
+	/// )
+	/// .unwrap();
+	///
+	/// assert!(info.supports(ServerFeature::WalTail));
+	/// assert!(!info.supports(ServerFeature::LoggerFollow));
+	/// ```
+	pub fn supports(&self, feature: ServerFeature) -> bool {
+		let version = match self.version_number() {
+			Some(v) => v,
+			None => return true,
+		};
+
+		feature.since().map(|v| version >= v).unwrap_or(true)
+			&& feature.until().map(|v| version < v).unwrap_or(true)
+	}
+
+	/// Fails if the server doesn't support the given [`ServerFeature`]
+	///
+	/// # Arguments
+	///
+	/// * `feature`: The [`ServerFeature`]
+	///
+	/// returns: `Result<()>`
+	pub(crate) fn require(&self, feature: ServerFeature) -> Result<()> {
+		if self.supports(feature) {
+			return Ok(());
+		}
+
+		let bound = match (feature.since(), feature.until()) {
+			(Some((major, minor)), _) if self.version_number() < feature.since() => {
+				format!("requires ArangoDB {}.{} or later", major, minor)
+			}
+			(_, Some((major, minor))) => format!("was removed on ArangoDB {}.{}", major, minor),
+			_ => "is not supported".to_string(),
+		};
+
+		Err(
+			Error::new(Kind::ArangoDB(ArangoDBError::UnsupportedServer)).with(format!(
+				"{} {}, the server runs {}",
+				feature.name(),
+				bound,
+				self.version
+			)),
+		)
+	}
+}
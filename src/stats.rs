@@ -0,0 +1,262 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Resolution of the sliding window buckets
+const BUCKET: Duration = Duration::from_secs(1);
+
+/// Maximum number of handler call durations kept to compute the latency percentiles
+const MAX_SAMPLES: usize = 1024;
+
+/// Document operation throughput of a [`Trigger`](`crate::Trigger`), returned by
+/// [`Trigger::stats`](`crate::Trigger::stats`)
+#[derive(Clone, Debug, Default)]
+pub struct TriggerStats {
+	/// The sliding window the `window_*` counters are measured over
+	pub window: Duration,
+	/// The [`CollectionStats`] indexed by collection name
+	pub collections: HashMap<String, CollectionStats>,
+	/// The [`HandlerStats`] indexed by handler type name
+	pub handlers: HashMap<String, HandlerStats>,
+}
+
+/// Document operation throughput of a single collection
+#[derive(Clone, Debug, Default)]
+pub struct CollectionStats {
+	/// Number of document operations dispatched since the Trigger was created
+	pub events: u64,
+	/// Size of the document data dispatched since the Trigger was created, in bytes
+	pub bytes: u64,
+	/// Number of document operations dispatched during the last [`TriggerStats::window`]
+	pub window_events: u64,
+	/// Size of the document data dispatched during the last [`TriggerStats::window`], in bytes
+	pub window_bytes: u64,
+}
+
+/// Execution statistics of a single [`Handler`](`crate::Handler`), to find out which handler
+/// and which documents cause pipeline stalls
+#[derive(Clone, Debug, Default)]
+pub struct HandlerStats {
+	/// Number of calls since the Trigger was created
+	pub calls: u64,
+	/// Median call duration during the last [`TriggerStats::window`], computed over the latest
+	/// calls only
+	pub p50: Duration,
+	/// 95th percentile call duration during the last [`TriggerStats::window`], computed over the
+	/// latest calls only
+	pub p95: Duration,
+	/// The slowest call during the last [`TriggerStats::window`], if any
+	pub slowest: Option<SlowEvent>,
+}
+
+/// A handler call on a single document operation, see [`HandlerStats::slowest`]
+#[derive(Clone, Debug)]
+pub struct SlowEvent {
+	/// The collection of the document operation
+	pub collection: String,
+	/// The key of the document, if present, see [`Trigger::with_key_path`]
+	///
+	/// [`Trigger::with_key_path`]: crate::Trigger::with_key_path
+	pub key: Option<String>,
+	/// The call duration
+	pub duration: Duration,
+}
+
+/// Recorder of the per-collection and per-handler counters behind [`TriggerStats`]
+pub(crate) struct StatsRecorder {
+	window: Duration,
+	collections: HashMap<String, CollectionRecorder>,
+	handlers: Mutex<HashMap<&'static str, HandlerRecorder>>,
+}
+
+/// Call durations of a single handler along with its slowest recent call
+#[derive(Default)]
+struct HandlerRecorder {
+	calls: u64,
+	samples: VecDeque<(Instant, Duration)>,
+	slowest: Option<(Instant, SlowEvent)>,
+}
+
+/// Counters of a single collection, the sliding window being kept as one second buckets
+#[derive(Default)]
+struct CollectionRecorder {
+	events: u64,
+	bytes: u64,
+	buckets: VecDeque<(Instant, u64, u64)>,
+}
+
+impl StatsRecorder {
+	/// Creates a new empty instance of `StatsRecorder`
+	///
+	/// # Arguments
+	///
+	/// * `window`: The duration of the sliding window
+	///
+	/// returns: [`StatsRecorder`]
+	pub(crate) fn new(window: Duration) -> Self {
+		Self {
+			window,
+			collections: HashMap::new(),
+			handlers: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Sets the duration of the sliding window
+	///
+	/// # Arguments
+	///
+	/// * `window`: The duration of the sliding window
+	pub(crate) fn set_window(&mut self, window: Duration) {
+		self.window = window;
+	}
+
+	/// Records one document operation
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The collection of the document operation
+	/// * `bytes`: The size of the document data
+	pub(crate) fn record(&mut self, collection: &str, bytes: u64) {
+		let now = Instant::now();
+		let window = self.window;
+
+		if !self.collections.contains_key(collection) {
+			self.collections
+				.insert(collection.to_string(), CollectionRecorder::default());
+		}
+
+		let recorder = self.collections.get_mut(collection).unwrap();
+
+		recorder.events += 1;
+		recorder.bytes += bytes;
+
+		match recorder.buckets.back_mut() {
+			Some((start, events, size)) if now.duration_since(*start) < BUCKET => {
+				*events += 1;
+				*size += bytes;
+			}
+			_ => recorder.buckets.push_back((now, 1, bytes)),
+		}
+
+		while let Some((start, _, _)) = recorder.buckets.front() {
+			if now.duration_since(*start) > window {
+				recorder.buckets.pop_front();
+			} else {
+				break;
+			}
+		}
+	}
+
+	/// Records one handler call
+	///
+	/// # Arguments
+	///
+	/// * `handler`: The handler type name
+	/// * `collection`: The collection of the dispatched document operation
+	/// * `duration`: The call duration
+	/// * `key`: Function extracting the document key, only called if the call is the slowest one
+	pub(crate) fn record_handler<K: FnOnce() -> Option<String>>(
+		&self,
+		handler: &'static str,
+		collection: &str,
+		duration: Duration,
+		key: K,
+	) {
+		let now = Instant::now();
+		let mut handlers = self.handlers.lock().unwrap();
+		let recorder = handlers.entry(handler).or_default();
+
+		recorder.calls += 1;
+		recorder.samples.push_back((now, duration));
+
+		if recorder.samples.len() > MAX_SAMPLES {
+			recorder.samples.pop_front();
+		}
+
+		let replace = match &recorder.slowest {
+			Some((at, slowest)) => {
+				duration > slowest.duration || now.duration_since(*at) > self.window
+			}
+			None => true,
+		};
+
+		if replace {
+			recorder.slowest = Some((
+				now,
+				SlowEvent {
+					collection: collection.to_string(),
+					key: key(),
+					duration,
+				},
+			));
+		}
+	}
+
+	/// Returns a snapshot of the recorded counters
+	///
+	/// returns: [`TriggerStats`]
+	pub(crate) fn snapshot(&self) -> TriggerStats {
+		let now = Instant::now();
+
+		TriggerStats {
+			window: self.window,
+			collections: self
+				.collections
+				.iter()
+				.map(|(name, recorder)| {
+					let (window_events, window_bytes) = recorder
+						.buckets
+						.iter()
+						.filter(|(start, _, _)| now.duration_since(*start) <= self.window)
+						.fold((0, 0), |(e, b), (_, events, bytes)| (e + events, b + bytes));
+
+					(
+						name.clone(),
+						CollectionStats {
+							events: recorder.events,
+							bytes: recorder.bytes,
+							window_events,
+							window_bytes,
+						},
+					)
+				})
+				.collect(),
+			handlers: self
+				.handlers
+				.lock()
+				.unwrap()
+				.iter()
+				.map(|(name, recorder)| {
+					let mut durations: Vec<Duration> = recorder
+						.samples
+						.iter()
+						.filter(|(at, _)| now.duration_since(*at) <= self.window)
+						.map(|(_, duration)| *duration)
+						.collect();
+
+					durations.sort();
+
+					let percentile = |q: f64| {
+						let idx = ((durations.len().saturating_sub(1)) as f64 * q).round();
+
+						durations.get(idx as usize).copied().unwrap_or_default()
+					};
+
+					(
+						name.to_string(),
+						HandlerStats {
+							calls: recorder.calls,
+							p50: percentile(0.5),
+							p95: percentile(0.95),
+							slowest: recorder
+								.slowest
+								.as_ref()
+								.filter(|(at, _)| now.duration_since(*at) <= self.window)
+								.map(|(_, slowest)| slowest.clone()),
+						},
+					)
+				})
+				.collect(),
+		}
+	}
+}
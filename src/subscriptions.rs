@@ -0,0 +1,600 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::api::DocumentOperation;
+#[cfg(feature = "metrics")]
+use crate::metrics::{HandlerMetricLabels, MetricsRegistry};
+use crate::observer::Observer;
+use crate::{
+	AsyncHandlerOutput, AsyncHandlerResult, DiagnosticCategory, DispatchOutcome, DispatchReport,
+	DuplicatePolicy, Error, Handler, HandlerContext, HandlerDispatch, HandlerEvent, HandlerResult,
+	Kind, OutputHandler, Result, Severity, SkipReason, SubscriptionError,
+};
+
+/// Dispatch method of a subscribed handler
+enum SubscriptionCallback {
+	/// [`Handler::dispatch`]
+	Handler(
+		for<'a> fn(
+			&'a HandlerContext<dyn Any + Send + Sync>,
+			&'a DocumentOperation,
+		) -> Option<AsyncHandlerOutput<'a>>,
+	),
+	/// [`OutputHandler::dispatch`]
+	Output(
+		for<'a> fn(
+			&'a HandlerContext<dyn Any + Send + Sync>,
+			&'a DocumentOperation,
+		) -> Option<AsyncHandlerResult<'a, ErasedOutput>>,
+	),
+}
+
+/// Type-erased value returned by an [`OutputHandler`]
+type ErasedOutput = Box<dyn Any + Send>;
+
+/// Event subscription
+pub(crate) struct Subscription {
+	handler: TypeId,
+	name: &'static str,
+	callback: SubscriptionCallback,
+	context: HandlerContext<dyn Any + Send + Sync>,
+}
+
+impl Subscription {
+	/// Creates a new [`Subscription`] of handler `H` with context `C`
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The handler [`SubscriptionCallback`]
+	/// * `ctx`: The handler's [`HandlerContext`]. Its type must be `C` or an error is returned
+	///
+	/// returns: `Result<Subscription>`
+	fn new<H: 'static, C: 'static>(
+		callback: SubscriptionCallback,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<Self> {
+		// Contexts are type-erased, so a context of the wrong type would silently prevent the
+		// handler from ever being called
+		if ctx.get_ref().type_id() != TypeId::of::<C>() {
+			return Err(
+				Error::new(Kind::Subscription(SubscriptionError::ContextMismatch))
+					.with(std::any::type_name::<H>()),
+			);
+		}
+
+		Ok(Self {
+			handler: TypeId::of::<H>(),
+			name: std::any::type_name::<H>(),
+			callback,
+			context: ctx,
+		})
+	}
+
+	/// Creates a new [`Subscription`] of a [`Handler`]
+	///
+	/// # Arguments
+	///
+	/// * `ctx`: The [`Handler`]'s [`HandlerContext`]. Its type must be the [`Handler::Context`]
+	///   one or an error is returned
+	///
+	/// returns: `Result<Subscription>`
+	pub(crate) fn handler<H: Handler>(ctx: HandlerContext<dyn Any + Send + Sync>) -> Result<Self> {
+		Self::new::<H, H::Context>(SubscriptionCallback::Handler(H::dispatch), ctx)
+	}
+
+	/// Creates a new [`Subscription`] of an [`OutputHandler`]
+	///
+	/// # Arguments
+	///
+	/// * `ctx`: The [`OutputHandler`]'s [`HandlerContext`]. Its type must be the
+	///   [`OutputHandler::Context`] one or an error is returned
+	///
+	/// returns: `Result<Subscription>`
+	pub(crate) fn output<H: OutputHandler>(
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<Self> {
+		Self::new::<H, H::Context>(SubscriptionCallback::Output(H::dispatch), ctx)
+	}
+}
+
+/// Event subscription map
+pub(crate) struct SubscriptionMap {
+	map: HashMap<HandlerEvent, Vec<Arc<Subscription>>>,
+}
+
+impl SubscriptionMap {
+	/// Creates a new empty instance of `SubscriptionMap`
+	///
+	/// returns: [`SubscriptionMap`]
+	pub(crate) fn empty() -> Self {
+		Self {
+			map: HashMap::new(),
+		}
+	}
+
+	/// Inserts into the inner map an instance of [`Subscription`]
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the handler is gonna listen to
+	/// * `subscription`: The handler's [`Subscription`]
+	/// * `duplicates`: The [`DuplicatePolicy`] to apply if the handler is already subscribed to
+	///   the [`HandlerEvent`]
+	/// * `observer`: The [`Observer`] to report duplicate subscriptions to
+	pub(crate) fn insert(
+		&mut self,
+		ev: HandlerEvent,
+		subscription: Subscription,
+		duplicates: DuplicatePolicy,
+		observer: &Observer,
+	) -> Result<()> {
+		let subscription = Arc::new(subscription);
+
+		let subs = self.map.entry(ev).or_default();
+
+		if let Some(idx) = subs.iter().position(|s| s.handler == subscription.handler) {
+			match duplicates {
+				DuplicatePolicy::Allow => observer.report(
+					Severity::Warning,
+					DiagnosticCategory::Handler,
+					format!(
+						"{:?} handler is subscribed twice to {:?} event",
+						subscription.name, ev
+					),
+				),
+				DuplicatePolicy::Ignore => return Ok(()),
+				DuplicatePolicy::Replace => {
+					subs[idx] = subscription;
+
+					return Ok(());
+				}
+				DuplicatePolicy::Error => {
+					return Err(Error::new(Kind::Subscription(SubscriptionError::Duplicate))
+						.with(subscription.name))
+				}
+			}
+		}
+
+		subs.push(subscription);
+
+		Ok(())
+	}
+
+	/// Moves all the [`Subscription`]s of another [`SubscriptionMap`] into this one
+	///
+	/// # Arguments
+	///
+	/// * `other`: The [`SubscriptionMap`] to be merged
+	pub(crate) fn merge(&mut self, other: SubscriptionMap) {
+		for (ev, subs) in other.map {
+			self.map.entry(ev).or_default().extend(subs);
+		}
+	}
+
+	/// Get all the [`Subscription`] instances attached to a [`HandlerEvent`]
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`]
+	///
+	/// returns: `Option<&Vec<Arc<Subscription>>>`
+	pub(crate) fn get(&self, ev: &HandlerEvent) -> Option<&Vec<Arc<Subscription>>> {
+		self.map.get(ev)
+	}
+}
+
+/// State of a [`SubscriptionManager`], guarded by its lock
+struct SubscriptionState {
+	collection_subscriptions: HashMap<String, SubscriptionMap>,
+	subscriptions: SubscriptionMap,
+	duplicates: DuplicatePolicy,
+	observer: Observer,
+}
+
+/// Subscription manager that will hold a general [`SubscriptionMap`] for all the events triggered
+/// and one [`SubscriptionMap`] per each collection-attached [`Subscription`] indexed by the
+/// collection string.
+///
+/// The manager is [`Send`] and [`Sync`] and shared behind an [`Arc`] with its
+/// [`Trigger`](`crate::Trigger`), see [`Trigger::subscriptions`], so handlers can be subscribed
+/// from any thread, even while the Trigger is listening. Dispatches already running are not
+/// affected by new subscriptions.
+///
+/// [`Trigger::subscriptions`]: crate::Trigger::subscriptions
+pub struct SubscriptionManager {
+	state: RwLock<SubscriptionState>,
+	#[cfg(feature = "metrics")]
+	pub(crate) metrics: Arc<MetricsRegistry>,
+}
+
+impl SubscriptionManager {
+	/// Creates a new instance of `SubscriptionManager`
+	///
+	/// returns: [`SubscriptionManager`]
+	pub(crate) fn new() -> Self {
+		Self {
+			state: RwLock::new(SubscriptionState {
+				collection_subscriptions: HashMap::new(),
+				subscriptions: SubscriptionMap::empty(),
+				duplicates: DuplicatePolicy::default(),
+				observer: Observer::default(),
+			}),
+			#[cfg(feature = "metrics")]
+			metrics: Arc::new(MetricsRegistry::new()),
+		}
+	}
+
+	/// Subscribes [`Handler`] to a [`HandlerEvent`] with a given [`HandlerContext`], see
+	/// [`Trigger::subscribe`](`crate::Trigger::subscribe`)
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
+	/// * `ctx`: The [`Handler`]'s [`HandlerContext`]. Its type must be the [`Handler::Context`] one
+	///   or an error is returned
+	///
+	/// returns: `Result<()>`
+	pub fn subscribe<H: Handler>(
+		&self,
+		ev: HandlerEvent,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.insert(ev, None, Subscription::handler::<H>(ctx)?)
+	}
+
+	/// Subscribes [`Handler`] to a [`HandlerEvent`] with a given [`HandlerContext`] for all the
+	/// document operations that affect the given collection, see
+	/// [`Trigger::subscribe_to`](`crate::Trigger::subscribe_to`)
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen for
+	/// * `collection`: The collection name the [`Handler`] is gonna listen for
+	/// * `ctx`: The [`Handler`]'s [`HandlerContext`]. Its type must be the [`Handler::Context`] one
+	///   or an error is returned
+	///
+	/// returns: `Result<()>`
+	pub fn subscribe_to<H: Handler>(
+		&self,
+		ev: HandlerEvent,
+		collection: &str,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.insert(ev, Some(collection), Subscription::handler::<H>(ctx)?)
+	}
+
+	/// Subscribes [`OutputHandler`] to a [`HandlerEvent`] with a given [`HandlerContext`], see
+	/// [`Trigger::subscribe_output`](`crate::Trigger::subscribe_output`)
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`OutputHandler`] is gonna listen to
+	/// * `ctx`: The [`OutputHandler`]'s [`HandlerContext`]. Its type must be the
+	///   [`OutputHandler::Context`] one or an error is returned
+	///
+	/// returns: `Result<()>`
+	pub fn subscribe_output<H: OutputHandler>(
+		&self,
+		ev: HandlerEvent,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.insert(ev, None, Subscription::output::<H>(ctx)?)
+	}
+
+	/// Subscribes [`OutputHandler`] to a [`HandlerEvent`] with a given [`HandlerContext`] for all
+	/// the document operations that affect the given collection, see
+	/// [`Trigger::subscribe_output_to`](`crate::Trigger::subscribe_output_to`)
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`OutputHandler`] is gonna listen for
+	/// * `collection`: The collection name the [`OutputHandler`] is gonna listen for
+	/// * `ctx`: The [`OutputHandler`]'s [`HandlerContext`]. Its type must be the
+	///   [`OutputHandler::Context`] one or an error is returned
+	///
+	/// returns: `Result<()>`
+	pub fn subscribe_output_to<H: OutputHandler>(
+		&self,
+		ev: HandlerEvent,
+		collection: &str,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.insert(ev, Some(collection), Subscription::output::<H>(ctx)?)
+	}
+
+	/// Inserts a [`Subscription`] into the [`SubscriptionMap`] of the given collection, or the
+	/// general one if [`None`]
+	fn insert(
+		&self,
+		ev: HandlerEvent,
+		collection: Option<&str>,
+		subscription: Subscription,
+	) -> Result<()> {
+		let mut state = self.state.write().unwrap();
+		let state = &mut *state;
+
+		let map = match collection {
+			Some(collection) => state
+				.collection_subscriptions
+				.entry(collection.to_string())
+				.or_insert_with(SubscriptionMap::empty),
+			None => &mut state.subscriptions,
+		};
+
+		map.insert(ev, subscription, state.duplicates, &state.observer)
+	}
+
+	/// Sets the [`DuplicatePolicy`] applied to new subscriptions
+	///
+	/// # Arguments
+	///
+	/// * `policy`: The [`DuplicatePolicy`]
+	pub(crate) fn set_duplicate_policy(&self, policy: DuplicatePolicy) {
+		self.state.write().unwrap().duplicates = policy;
+	}
+
+	/// Sets the [`Observer`] duplicate subscriptions and failed dispatches are reported to
+	///
+	/// # Arguments
+	///
+	/// * `observer`: The [`Observer`]
+	pub(crate) fn set_observer(&self, observer: Observer) {
+		self.state.write().unwrap().observer = observer;
+	}
+
+	/// Returns the names of all the collections with attached [`Subscription`]s
+	///
+	/// returns: `Vec<String>`
+	pub(crate) fn collections(&self) -> Vec<String> {
+		self.state
+			.read()
+			.unwrap()
+			.collection_subscriptions
+			.keys()
+			.cloned()
+			.collect()
+	}
+
+	/// Re-points all the [`Subscription`]s attached to a collection to a new collection name,
+	/// merging them with the ones already attached to the new name if any
+	///
+	/// # Arguments
+	///
+	/// * `from`: The old collection name
+	/// * `to`: The new collection name
+	pub(crate) fn rename_collection(&self, from: &str, to: &str) {
+		let mut state = self.state.write().unwrap();
+
+		if let Some(map) = state.collection_subscriptions.remove(from) {
+			if let Some(subs) = state.collection_subscriptions.get_mut(to) {
+				subs.merge(map)
+			} else {
+				state.collection_subscriptions.insert(to.to_string(), map);
+			}
+		}
+	}
+
+	/// Returns the [`HandlerEvent`]s the given handler is subscribed to for the document
+	/// operations of a collection, either globally or attached to the collection
+	///
+	/// # Arguments
+	///
+	/// * `handler`: The handler [`TypeId`]
+	/// * `collection`: The collection name
+	///
+	/// returns: `Vec<HandlerEvent>`
+	pub(crate) fn handler_events(&self, handler: TypeId, collection: &str) -> Vec<HandlerEvent> {
+		let state = self.state.read().unwrap();
+
+		[
+			HandlerEvent::InsertOrReplace,
+			HandlerEvent::Remove,
+			HandlerEvent::CollectionReset,
+		]
+		.into_iter()
+		.filter(|ev| {
+			[
+				state.subscriptions.get(ev),
+				state
+					.collection_subscriptions
+					.get(collection)
+					.and_then(|map| map.get(ev)),
+			]
+			.into_iter()
+			.flatten()
+			.flatten()
+			.any(|sub| sub.handler == handler)
+		})
+		.collect()
+	}
+
+	/// Triggers all the [`Subscription`]s for a [`HandlerEvent`] with the possibility of also
+	/// triggering all the [`Subscription`]s for the same [`HandlerEvent`] and a specific
+	/// collection. It will also give to the [`Subscription`] callback the [`DocumentOperation`]
+	/// data of the event
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] to be triggered
+	/// * `doc`: The [`DocumentOperation`] data
+	/// * `collection`: [`Some`] to trigger collection-attached [`Subscription`]  callbacks or
+	///   [`None`] to trigger only global [`Subscription`] callbacks
+	/// * `skip`: [`Some`] to skip all the [`Subscription`] callbacks, reporting them as skipped
+	///   with the given [`SkipReason`]
+	/// * `results`: The callback [`OutputHandler`] results are forwarded to, if any
+	/// * `only`: [`Some`] to trigger only the [`Subscription`]s of the handler with the given
+	///   [`TypeId`]
+	///
+	/// returns: [`DispatchReport`]
+	pub(crate) async fn call(
+		&self,
+		ev: HandlerEvent,
+		doc: &DocumentOperation,
+		collection: Option<&str>,
+		skip: Option<SkipReason>,
+		results: Option<&(dyn Fn(HandlerResult) + Send + Sync)>,
+		only: Option<TypeId>,
+	) -> DispatchReport {
+		let mut report = DispatchReport {
+			event: ev,
+			collection: doc.collection.clone(),
+			key: None,
+			handlers: Vec::new(),
+		};
+
+		// Take a snapshot of the subscriptions so the lock isn't held while handlers run
+		let (global, scoped, observer) = {
+			let state = self.state.read().unwrap();
+
+			let global = state.subscriptions.get(&ev).cloned().unwrap_or_default();
+			let scoped = collection
+				.and_then(|col| state.collection_subscriptions.get(col))
+				.and_then(|map| map.get(&ev))
+				.cloned()
+				.unwrap_or_default();
+
+			(global, scoped, state.observer.clone())
+		};
+
+		let (global, scoped) = match only {
+			Some(handler) => (
+				global
+					.into_iter()
+					.filter(|s| s.handler == handler)
+					.collect(),
+				scoped
+					.into_iter()
+					.filter(|s| s.handler == handler)
+					.collect(),
+			),
+			None => (global, scoped),
+		};
+
+		// Call generic subscriptions with no collection attached
+		let mut outputs = self
+			.dispatch_event(&global, doc, false, skip, &observer, &mut report)
+			.await;
+
+		// Call subscriptions for specific collection if matches
+		outputs.extend(
+			self.dispatch_event(&scoped, doc, true, skip, &observer, &mut report)
+				.await,
+		);
+
+		if let Some(results) = results {
+			for output in outputs {
+				results(output);
+			}
+		}
+
+		report
+	}
+
+	/// Calls the given [`Subscription`]s attached to a [`HandlerEvent`], adding their outcome to
+	/// the [`DispatchReport`]
+	///
+	/// returns: `Vec<HandlerResult>`, the values returned by the [`OutputHandler`]s
+	async fn dispatch_event(
+		&self,
+		subs: &[Arc<Subscription>],
+		doc: &DocumentOperation,
+		scoped: bool,
+		skip: Option<SkipReason>,
+		observer: &Observer,
+		report: &mut DispatchReport,
+	) -> Vec<HandlerResult> {
+		let mut outputs = Vec::new();
+
+		#[cfg(feature = "metrics")]
+		let e = &report.event;
+
+		for sub in subs {
+			if let Some(reason) = skip {
+				report.handlers.push(HandlerDispatch {
+					handler: sub.name,
+					scoped,
+					outcome: DispatchOutcome::Skipped(reason),
+					duration: Duration::ZERO,
+				});
+
+				continue;
+			}
+
+			let start = Instant::now();
+
+			// Context types are checked when subscribing, see Subscription::new, but a handler
+			// overriding its dispatch method can still return no future
+			let called = match sub.callback {
+				SubscriptionCallback::Handler(callback) => match callback(&sub.context, doc) {
+					Some(cb) => {
+						cb.await;
+						true
+					}
+					None => false,
+				},
+				SubscriptionCallback::Output(callback) => match callback(&sub.context, doc) {
+					Some(cb) => {
+						outputs.push(HandlerResult::new(
+							sub.name,
+							report.event,
+							doc.collection.clone(),
+							cb.await,
+						));
+
+						true
+					}
+					None => false,
+				},
+			};
+
+			let outcome = if called {
+				#[cfg(feature = "metrics")]
+				self.record_metrics(sub, e, doc, start);
+
+				DispatchOutcome::Called
+			} else {
+				observer.report(
+					Severity::Error,
+					DiagnosticCategory::Handler,
+					format!(
+						"{:?} handler dispatch returned no future, skipping it",
+						sub.name
+					),
+				);
+
+				DispatchOutcome::Skipped(SkipReason::ContextMismatch)
+			};
+
+			report.handlers.push(HandlerDispatch {
+				handler: sub.name,
+				scoped,
+				outcome,
+				duration: start.elapsed(),
+			});
+		}
+
+		outputs
+	}
+
+	/// Records the metrics of a single [`Subscription`] invocation labelled by its handler name,
+	/// the document collection and the [`HandlerEvent`]
+	#[cfg(feature = "metrics")]
+	fn record_metrics(
+		&self,
+		sub: &Subscription,
+		e: &HandlerEvent,
+		doc: &DocumentOperation,
+		start: Instant,
+	) {
+		self.metrics.record(
+			HandlerMetricLabels {
+				handler: sub.name.to_string(),
+				collection: doc.collection.clone(),
+				event: *e,
+			},
+			start.elapsed(),
+		)
+	}
+}
@@ -0,0 +1,188 @@
+use std::result::Result as StdResult;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName};
+use rustls_pemfile::Item;
+
+use crate::{Error, Kind, Result};
+
+/// TLS configuration of the [`Trigger`](`crate::Trigger`) HTTP client. By default server
+/// certificates are verified against the Mozilla root certificates.
+///
+/// # Examples
+///
+/// ```no_run
+/// use arangodb_events_rs::{Trigger, TriggerTlsConfig};
+///
+/// let ca = std::fs::read("/etc/arangodb/ca.pem").unwrap();
+///
+/// let mut trigger = Trigger::new(
+///     "https://arangodb.internal:8529/",
+///     "alchemy",
+/// )
+/// .with_tls_config(
+///     TriggerTlsConfig::new()
+///         .with_root_certificate_pem(&ca)
+///         .expect("Invalid CA certificate"),
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct TriggerTlsConfig {
+	root_certificates: Vec<Certificate>,
+	accept_invalid_certs: bool,
+	client_auth: Option<(Vec<Certificate>, PrivateKey)>,
+}
+
+impl TriggerTlsConfig {
+	/// Creates a new default [`TriggerTlsConfig`]
+	///
+	/// returns: [`TriggerTlsConfig`]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Trusts the certificates of a PEM bundle on top of the Mozilla root certificates, e.g. the
+	/// private CA of an on-premise cluster
+	///
+	/// # Arguments
+	///
+	/// * `pem`: The PEM encoded certificates
+	///
+	/// returns: `Result<TriggerTlsConfig>`, failing if the bundle has no valid certificates
+	pub fn with_root_certificate_pem(mut self, pem: &[u8]) -> Result<Self> {
+		let certs = rustls_pemfile::certs(&mut &*pem).map_err(|e| Error::new(Kind::Tls).with(e))?;
+
+		if certs.is_empty() {
+			return Err(Error::new(Kind::Tls).with("no certificates found on PEM bundle"));
+		}
+
+		self.root_certificates
+			.extend(certs.into_iter().map(Certificate));
+
+		Ok(self)
+	}
+
+	/// Sets the client certificate chain and private key presented to the server, for deployments
+	/// requiring mutual TLS to reach the coordinator
+	///
+	/// # Arguments
+	///
+	/// * `cert_pem`: The PEM encoded client certificate chain, leaf first
+	/// * `key_pem`: The PEM encoded private key, either PKCS#8, PKCS#1 (RSA) or SEC1 (EC)
+	///
+	/// returns: `Result<TriggerTlsConfig>`, failing if no certificate or private key is found
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use arangodb_events_rs::TriggerTlsConfig;
+	///
+	/// let cert = std::fs::read("/etc/arangodb/client.pem").unwrap();
+	/// let key = std::fs::read("/etc/arangodb/client.key").unwrap();
+	///
+	/// let config = TriggerTlsConfig::new()
+	///     .with_client_certificate_pem(&cert, &key)
+	///     .expect("Invalid client certificate");
+	/// ```
+	pub fn with_client_certificate_pem(mut self, cert_pem: &[u8], key_pem: &[u8]) -> Result<Self> {
+		let certs =
+			rustls_pemfile::certs(&mut &*cert_pem).map_err(|e| Error::new(Kind::Tls).with(e))?;
+
+		if certs.is_empty() {
+			return Err(
+				Error::new(Kind::Tls).with("no certificates found on client certificate PEM")
+			);
+		}
+
+		let mut reader = key_pem;
+		let key = loop {
+			match rustls_pemfile::read_one(&mut reader)
+				.map_err(|e| Error::new(Kind::Tls).with(e))?
+			{
+				Some(Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key)) => break key,
+				Some(_) => continue,
+				None => {
+					return Err(Error::new(Kind::Tls).with("no private key found on client key PEM"))
+				}
+			}
+		};
+
+		self.client_auth = Some((
+			certs.into_iter().map(Certificate).collect(),
+			PrivateKey(key),
+		));
+
+		Ok(self)
+	}
+
+	/// Disables server certificate verification, accepting self-signed or expired certificates.
+	/// **This is dangerous**, only use it against development clusters
+	///
+	/// # Arguments
+	///
+	/// * `accept`: Whether invalid certificates should be accepted
+	///
+	/// returns: [`TriggerTlsConfig`]
+	pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+		self.accept_invalid_certs = accept;
+		self
+	}
+
+	/// Builds the `rustls` client configuration
+	///
+	/// returns: `Result<ClientConfig>`
+	pub(crate) fn build(&self) -> Result<ClientConfig> {
+		let mut roots = RootCertStore::empty();
+
+		roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+			OwnedTrustAnchor::from_subject_spki_name_constraints(
+				ta.subject,
+				ta.spki,
+				ta.name_constraints,
+			)
+		}));
+
+		for cert in &self.root_certificates {
+			roots.add(cert).map_err(|e| Error::new(Kind::Tls).with(e))?;
+		}
+
+		let builder = ClientConfig::builder()
+			.with_safe_defaults()
+			.with_root_certificates(roots);
+
+		let mut config = match &self.client_auth {
+			Some((certs, key)) => builder
+				.with_client_auth_cert(certs.clone(), key.clone())
+				.map_err(|e| Error::new(Kind::Tls).with(e))?,
+			None => builder.with_no_client_auth(),
+		};
+
+		if self.accept_invalid_certs {
+			config
+				.dangerous()
+				.set_certificate_verifier(Arc::new(AcceptAnyCertificate));
+		}
+
+		Ok(config)
+	}
+}
+
+/// Server certificate verifier accepting any certificate, see
+/// [`TriggerTlsConfig::danger_accept_invalid_certs`]
+struct AcceptAnyCertificate;
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+	fn verify_server_cert(
+		&self,
+		_end_entity: &Certificate,
+		_intermediates: &[Certificate],
+		_server_name: &ServerName,
+		_scts: &mut dyn Iterator<Item = &[u8]>,
+		_ocsp_response: &[u8],
+		_now: SystemTime,
+	) -> StdResult<ServerCertVerified, rustls::Error> {
+		Ok(ServerCertVerified::assertion())
+	}
+}
@@ -1,13 +1,36 @@
+use futures_util::future::join_all;
+use hyper::header::{HeaderName, HeaderValue};
 use hyper::http::request::Builder as HttpRequestBuilder;
-use hyper::{Body, Client, Request, Response, StatusCode, Uri};
-use std::any::Any;
+use hyper::{Body, Client, HeaderMap, Method, Request, Response, StatusCode, Uri};
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use std::any::{Any, TypeId};
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::api::{DocumentOperation, LogType, LoggerStateData};
+use crate::api::{
+	AuthResponse, BarrierResponse, ClusterEndpoints, CollectionNames, CollectionsResponse,
+	CursorResponse, DdlKind, DocumentOperation, ErrorResponse, Event, LogType, LoggerStateData,
+	TickRange,
+};
+use crate::config::ConfigWatch;
 use crate::deserialize::Deserializer;
+#[cfg(feature = "metrics")]
+use crate::metrics::{HandlerMetricLabels, HandlerMetrics};
+use crate::observer::Observer;
+use crate::proxy::ProxyConnector;
+use crate::stats::StatsRecorder;
 use crate::{
-	utils, Error, Handler, HandlerContext, HandlerEvent, Io, Kind, MapCrateError, Result,
-	SubscriptionManager,
+	utils, ArangoDBError, CatchUpProgress, ConfigStore, Diagnostic, DiagnosticCategory,
+	DispatchMode, DispatchOutcome, DispatchReport, DuplicatePolicy, Error, Handler, HandlerContext,
+	HandlerEvent, HandlerResult, Io, Kind, LagThreshold, LogApi, MapCrateError, OpenTransaction,
+	OutputHandler, PollScheduler, PollStats, Result, ResumeToken, RetryPolicy, ServerInfo,
+	Severity, SkipReason, StalePolicy, StartupProbe, SubscriptionManager, TickStore, TriggerHandle,
+	TriggerHealth, TriggerHttpConfig, TriggerLag, TriggerProgress, TriggerProxy, TriggerStats,
+	TriggerThread, UserDocument, UserEvent, USERS_COLLECTION,
 };
 
 /// ArangoDB HTTP Header. From the ArangoDB docs: it's the tick value of the last included value in
@@ -17,6 +40,86 @@ use crate::{
 /// log events from the start of the log again).
 const LAST_LOG_HEADER: &str = "X-Arango-Replication-Lastincluded";
 
+/// ArangoDB HTTP Header. From the ArangoDB docs: whether or not there are more log entries
+/// available. If set to `false` the log has been fully consumed up to the server's last tick.
+const CHECK_MORE_HEADER: &str = "X-Arango-Replication-Checkmore";
+
+/// ArangoDB HTTP Header. From the ArangoDB docs: the last tick the server scanned while computing
+/// the result, which can be higher than the last included tick. Only sent by **`wal/tail`**, which
+/// gets it back on the `lastScanned` parameter of the next request to avoid scanning the same WAL
+/// range again
+const LAST_SCANNED_HEADER: &str = "X-Arango-Replication-Lastscanned";
+
+/// ArangoDB HTTP Header. From the ArangoDB docs: set to `true` if the server returned all tick
+/// values starting from the specified tick in the `from` parameter. Should this be set to `false`
+/// the server did not have these operations anymore and the client might have missed operations.
+const FROM_PRESENT_HEADER: &str = "X-Arango-Replication-Frompresent";
+
+/// ArangoDB error number returned when the requested database doesn't exist
+const DATABASE_NOT_FOUND_ERROR: u32 = 1228;
+
+/// ArangoDB HTTP Header. From the ArangoDB docs: the last tick value the logger server has logged
+/// (not necessarily included in the result). By comparing the last tick and last included tick
+/// values, clients have an approximate indication of how many events there are still left to
+/// fetch.
+const LAST_TICK_HEADER: &str = "X-Arango-Replication-Lasttick";
+
+/// Smallest `chunkSize` requested when shrinking it after a response exceeded the maximum body
+/// size, see [`Trigger::with_max_body_size`]
+const MIN_CHUNK_SIZE: u64 = 16 * 1024;
+
+/// Weight of the latest measurement on the average tick rate the catch-up ETA is estimated from,
+/// see [`Trigger::on_catch_up_progress`]
+const CATCH_UP_RATE_WEIGHT: f64 = 0.2;
+
+/// HTTP connector of the [`Trigger`] client
+#[cfg(not(feature = "tls"))]
+type Connector = ProxyConnector;
+
+/// HTTP connector of the [`Trigger`] client, supporting both HTTP and HTTPS
+#[cfg(feature = "tls")]
+type Connector = hyper_rustls::HttpsConnector<ProxyConnector>;
+
+/// Callback invoked once the [`Trigger`] has caught up with the server log
+type CaughtUpCallback = Box<dyn Fn() + Send + Sync>;
+
+/// Callback invoked once the [`Trigger`] has re-initialized after its database got recreated
+type DatabaseResetCallback = Box<dyn Fn() + Send + Sync>;
+
+/// Callback invoked when the [`Trigger`] lag goes beyond its [`LagThreshold`]
+type LagAlertCallback = Box<dyn Fn(&TriggerLag) + Send + Sync>;
+
+/// Callback invoked after each event dispatch with its [`DispatchReport`]
+type DispatchCallback = Box<dyn Fn(&DispatchReport) + Send + Sync>;
+
+/// Type alias for the callback receiving the [`OutputHandler`] results
+type ResultCallback = Box<dyn Fn(HandlerResult) + Send + Sync>;
+
+/// Callback invoked with the HTTP Headers of each **`logger-follow`** or **`wal/tail`** response
+type ResponseHeadersCallback = Box<dyn Fn(&HeaderMap) + Send + Sync>;
+
+/// Callback invoked with every [`Event`] received
+type EventCallback = Box<dyn Fn(&Event) + Send + Sync>;
+
+/// Callback invoked when a watched collection received no events for too long, with the collection
+/// name and the time elapsed since its last event
+type SilenceCallback = Box<dyn Fn(&str, Duration) + Send + Sync>;
+
+/// Callback invoked with every change of the `_users` system collection
+type UserCallback = Box<dyn Fn(&UserEvent) + Send + Sync>;
+
+/// Callback invoked every N dispatched events with the [`TriggerProgress`]
+type ProgressCallback = Box<dyn Fn(&TriggerProgress) + Send + Sync>;
+
+/// Callback invoked periodically with the [`CatchUpProgress`] while catching up
+type CatchUpCallback = Box<dyn Fn(&CatchUpProgress) + Send + Sync>;
+
+/// Callback invoked after each `listen()` call with its [`PollStats`]
+type PollCallback = Box<dyn Fn(&PollStats) + Send + Sync>;
+
+/// Function extracting the deadline of a document operation
+type DeadlineExtractor = Box<dyn Fn(&DocumentOperation) -> Option<SystemTime> + Send + Sync>;
+
 /// ArangoDB event trigger. This structs contains server-related information and its
 /// [`Subscription`]s. Use it in order to listen to ArangoDB events.
 ///
@@ -29,10 +132,10 @@ const LAST_LOG_HEADER: &str = "X-Arango-Replication-Lastincluded";
 /// use arangodb_events_rs::{Trigger, TriggerAuthentication};
 ///
 /// let mut trigger = Trigger::new_auth(
-///    	"http://localhost:8529/",
-///    	"alchemy",
-///    	TriggerAuthentication::new("user", "password"),
-///	);
+///     "http://localhost:8529/",
+///     "alchemy",
+///     TriggerAuthentication::new("user", "password"),
+/// );
 /// ```
 ///
 /// ## Unauthenticated ArangoDB instance
@@ -40,23 +143,100 @@ const LAST_LOG_HEADER: &str = "X-Arango-Replication-Lastincluded";
 /// use arangodb_events_rs::Trigger;
 ///
 /// let mut trigger = Trigger::new(
-///    	"http://localhost:8529/",
-///    	"alchemy",
-///	);
+///     "http://localhost:8529/",
+///     "alchemy",
+/// );
 /// ```
 pub struct Trigger {
-	host: String,
+	hosts: RwLock<Vec<String>>,
+	active_host: AtomicUsize,
+	leader_discovery: bool,
 	database: String,
 	auth: Option<TriggerAuthentication>,
 	last_log_tick: String,
-	transactions: HashMap<String, Transaction>,
-	subscriptions: SubscriptionManager,
+	log: LogState,
+	subscriptions: Arc<SubscriptionManager>,
+	observer: Observer,
+	priorities: HashMap<String, i32>,
+	caught_up: bool,
+	on_caught_up: Option<CaughtUpCallback>,
+	lag: TriggerLag,
+	behind_since: Option<Instant>,
+	health: TriggerHealth,
+	lag_threshold: Option<LagThreshold>,
+	on_lag_alert: Option<LagAlertCallback>,
+	silence_watchdogs: Vec<SilenceWatchdog>,
+	track_renames: bool,
+	on_dispatch: Option<DispatchCallback>,
+	on_result: Option<ResultCallback>,
+	dispatch_mode: DispatchMode,
+	on_response_headers: Option<ResponseHeadersCallback>,
+	on_event: Option<EventCallback>,
+	on_user_event: Option<UserCallback>,
+	users: HashMap<String, UserDocument>,
+	configs: Vec<(String, Box<dyn ConfigWatch>)>,
+	deadline: Option<(DeadlineExtractor, StalePolicy)>,
+	retry: Option<Box<dyn RetryPolicy>>,
+	scheduler: Option<PollScheduler>,
+	dry_run: bool,
+	max_batch_size: Option<usize>,
+	max_body_size: Option<u64>,
+	chunk_size: u64,
+	dispatched: u64,
+	on_progress: Option<(u64, ProgressCallback)>,
+	on_catch_up: Option<(Duration, CatchUpCallback)>,
+	catch_up: Option<CatchUp>,
+	events_per_tick: f64,
+	last_poll: PollStats,
+	on_poll: Option<PollCallback>,
+	server_id: Option<String>,
+	resume: Option<ResumeToken>,
+	tick_store: Option<Box<dyn TickStore>>,
+	saved_tick: Option<String>,
+	checkpoint_transactions: bool,
+	barrier_ttl: Option<Duration>,
+	barrier_id: Option<String>,
+	barrier_extended: Option<Instant>,
+	handle: TriggerHandle,
+	startup_probe: Option<StartupProbe>,
+	probe_pending: bool,
+	stats: StatsRecorder,
+	headers: HeaderMap,
+	auto_reinit: bool,
+	database_missing: bool,
+	on_database_reset: Option<DatabaseResetCallback>,
+	proxy: Option<TriggerProxy>,
+	seen_tick: u64,
+	log_api: LogApi,
+	server_info: Option<ServerInfo>,
+	last_scanned: u64,
+	key_paths: HashMap<String, String>,
+	http2: bool,
+	client: OnceLock<Client<Connector>>,
+	connect_timeout: Option<Duration>,
+	read_timeout: Option<Duration>,
+	http: TriggerHttpConfig,
+	#[cfg(feature = "tls")]
+	tls: crate::TriggerTlsConfig,
 }
 
-/// ArangoDB struct holding basic HTTP ArangoDB server authentication
+/// ArangoDB struct holding HTTP ArangoDB server authentication, either Basic or JWT
 pub struct TriggerAuthentication {
-	user: String,
-	password: String,
+	method: AuthenticationMethod,
+}
+
+/// HTTP authentication method of a [`TriggerAuthentication`]
+enum AuthenticationMethod {
+	/// HTTP Basic authentication with username and password
+	Basic { user: String, password: String },
+	/// JWT sent as a bearer token
+	Jwt(String),
+	/// JWT obtained from the server with username and password, renewed when it expires
+	JwtLogin {
+		user: String,
+		password: String,
+		token: Mutex<Option<String>>,
+	},
 }
 
 impl TriggerAuthentication {
@@ -83,8 +263,69 @@ impl TriggerAuthentication {
 	/// ```
 	pub fn new(user: &str, password: &str) -> Self {
 		Self {
-			user: user.to_string(),
-			password: password.to_string(),
+			method: AuthenticationMethod::Basic {
+				user: user.to_string(),
+				password: password.to_string(),
+			},
+		}
+	}
+
+	/// Creates a new instance of [`TriggerAuthentication`] that sends a JWT as
+	/// `Authorization: bearer` HTTP Header, for ArangoDB servers with Basic authentication disabled
+	///
+	/// # Arguments
+	///
+	/// * `token`: The JWT
+	///
+	/// returns: [`TriggerAuthentication`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{Trigger, TriggerAuthentication};
+	///
+	/// let mut trigger = Trigger::new_auth(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	///     TriggerAuthentication::jwt("eyJhbGciOiJIUzI1NiIs..."),
+	/// );
+	/// ```
+	pub fn jwt(token: &str) -> Self {
+		Self {
+			method: AuthenticationMethod::Jwt(token.to_string()),
+		}
+	}
+
+	/// Creates a new instance of [`TriggerAuthentication`] that logs in to the ArangoDB server
+	/// calling **`POST /_open/auth`** with the given credentials and sends the returned JWT as
+	/// `Authorization: bearer` HTTP Header. The JWT is transparently renewed whenever the server
+	/// responds `401 Unauthorized`.
+	///
+	/// # Arguments
+	///
+	/// * `user`: The ArangoDB server username
+	/// * `password`: The ArangoDB server password
+	///
+	/// returns: [`TriggerAuthentication`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{Trigger, TriggerAuthentication};
+	///
+	/// let mut trigger = Trigger::new_auth(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	///     TriggerAuthentication::jwt_login("user", "password"),
+	/// );
+	/// ```
+	pub fn jwt_login(user: &str, password: &str) -> Self {
+		Self {
+			method: AuthenticationMethod::JwtLogin {
+				user: user.to_string(),
+				password: password.to_string(),
+				token: Mutex::new(None),
+			},
 		}
 	}
 }
@@ -106,30 +347,94 @@ impl Trigger {
 	/// use arangodb_events_rs::Trigger;
 	///
 	/// let mut trigger = Trigger::new(
-	///    	"http://localhost:8529/",
-	///    	"alchemy",
-	///	);
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
 	/// ```
 	pub fn new(host: &str, database: &str) -> Self {
 		Self {
-			host: host.to_string(),
+			hosts: RwLock::new(vec![host.to_string()]),
+			active_host: AtomicUsize::new(0),
+			leader_discovery: false,
 			database: database.to_string(),
 			auth: None,
 			last_log_tick: "0".to_string(),
-			transactions: HashMap::new(),
-			subscriptions: SubscriptionManager::new(),
+			log: LogState::default(),
+			subscriptions: Arc::new(SubscriptionManager::new()),
+			observer: Observer::default(),
+			priorities: HashMap::new(),
+			caught_up: false,
+			on_caught_up: None,
+			lag: TriggerLag::default(),
+			behind_since: None,
+			health: TriggerHealth::Healthy,
+			lag_threshold: None,
+			on_lag_alert: None,
+			silence_watchdogs: Vec::new(),
+			track_renames: true,
+			on_dispatch: None,
+			on_result: None,
+			dispatch_mode: DispatchMode::default(),
+			on_response_headers: None,
+			on_event: None,
+			on_user_event: None,
+			users: HashMap::new(),
+			configs: Vec::new(),
+			deadline: None,
+			retry: None,
+			scheduler: None,
+			dry_run: false,
+			max_batch_size: None,
+			max_body_size: None,
+			chunk_size: 0,
+			dispatched: 0,
+			on_progress: None,
+			on_catch_up: None,
+			catch_up: None,
+			events_per_tick: 0.0,
+			last_poll: PollStats::default(),
+			on_poll: None,
+			server_id: None,
+			resume: None,
+			tick_store: None,
+			saved_tick: None,
+			checkpoint_transactions: false,
+			barrier_ttl: None,
+			barrier_id: None,
+			barrier_extended: None,
+			handle: TriggerHandle::default(),
+			startup_probe: None,
+			probe_pending: false,
+			stats: StatsRecorder::new(std::time::Duration::from_secs(60)),
+			headers: HeaderMap::new(),
+			auto_reinit: false,
+			database_missing: false,
+			on_database_reset: None,
+			proxy: None,
+			seen_tick: 0,
+			log_api: LogApi::default(),
+			server_info: None,
+			last_scanned: 0,
+			key_paths: HashMap::new(),
+			http2: false,
+			client: OnceLock::new(),
+			connect_timeout: None,
+			read_timeout: None,
+			http: TriggerHttpConfig::default(),
+			#[cfg(feature = "tls")]
+			tls: crate::TriggerTlsConfig::default(),
 		}
 	}
 
 	/// Creates a new [`Trigger`] instance that connects to an ArangoDB HTTP Server by the given
-	/// details with HTTP Basic authentication.
+	/// details with HTTP authentication.
 	///
 	/// # Arguments
 	///
 	/// * `host`: The ArangoDB server instance host
 	/// * `database`: The ArangoDB server instance database name
-	/// * `auth`: The ArangoDB HTTP basic authentication details held on a [`TriggerAuthentication`]
-	/// struct
+	/// * `auth`: The ArangoDB HTTP authentication details held on a [`TriggerAuthentication`]
+	///   struct
 	///
 	/// returns: [`Trigger`]
 	///
@@ -139,9 +444,9 @@ impl Trigger {
 	/// use arangodb_events_rs::Trigger;
 	///
 	/// let mut trigger = Trigger::new(
-	///    	"http://localhost:8529/",
-	///    	"alchemy",
-	///	);
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
 	/// ```
 	pub fn new_auth(host: &str, database: &str, auth: TriggerAuthentication) -> Self {
 		let mut instance = Self::new(host, database);
@@ -149,42 +454,46 @@ impl Trigger {
 		instance
 	}
 
-	/// Gets HTTP URI for the given endpoint with the host and database stored
-	fn get_uri(&self, endpoint: &str) -> Result<Uri> {
-		format!("{}/_db/{}{}", self.host, self.database, endpoint)
-			.parse()
-			.map_err(|e: hyper::http::uri::InvalidUri| e.into())
-	}
-
-	/// Retrieves `Authorization` HTTP Header value by a [`TriggerAuthentication`]
-	fn get_authorization_value(&self, auth: &TriggerAuthentication) -> String {
-		format!(
-			"Basic {}",
-			base64::encode(format!("{}:{}", auth.user, auth.password))
-		)
-	}
-
-	/// Creates a [`HttpRequestBuilder`] with the given [`Uri`]
-	fn get_new_request(&self, uri: Uri) -> HttpRequestBuilder {
-		let mut req = Request::builder().uri(uri);
-
-		if let Some(auth) = &self.auth {
-			req = req.header(
-				hyper::header::AUTHORIZATION,
-				self.get_authorization_value(auth),
-			);
-		}
-
-		req
+	/// Assigns a dispatch priority to a collection. When any priority is set, the document
+	/// operations of each [`Trigger::listen`] batch are buffered and dispatched from the highest to the
+	/// lowest priority instead of in log order, so critical collections get processed first while
+	/// catching up. Operations on the same priority keep their log order and collections without
+	/// an assigned priority default to `0`. Document operations are never moved across a database,
+	/// collection, index or view operation of the batch.
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The ArangoDB collection name
+	/// * `priority`: The collection priority, higher values get dispatched first
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_collection_priority("orders", 10)
+	/// .with_collection_priority("metrics_raw", -10);
+	/// ```
+	pub fn with_collection_priority(mut self, collection: &str, priority: i32) -> Self {
+		self.priorities.insert(collection.to_string(), priority);
+		self
 	}
 
-	/// Initializes a [`Trigger`]. This method calls **`GET /_api/replication/logger-state`**
-	/// endpoint on the ArangoDB server to store the last log tick from ArangoDB Replication API on
-	/// the [`Trigger`] instance to then be used on the [`listen`] method.
+	/// Sets a callback invoked only once, the first time the [`Trigger`] has processed all the log
+	/// events that existed on the server when it started listening. This is useful to delay
+	/// marking a service as ready until its local state is current.
 	///
-	/// [`listen`]: #method.listen
+	/// # Arguments
 	///
-	/// returns: `Result<()>`
+	/// * `callback`: The function to be called once caught up
+	///
+	/// returns: [`Trigger`]
 	///
 	/// # Examples
 	///
@@ -192,359 +501,4303 @@ impl Trigger {
 	/// use arangodb_events_rs::Trigger;
 	///
 	/// let mut trigger = Trigger::new(
-	///    	"http://localhost:8529/",
-	///    	"alchemy",
-	///	);
-	///
-	/// trigger.init().await.expect("Error initializing ArangoDB event trigger");
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_caught_up(|| println!("Trigger is up to date"));
 	/// ```
-	pub async fn init(&mut self) -> Result<()> {
-		let client = Client::new();
-
-		let logger_state_uri = self.get_uri("/_api/replication/logger-state")?;
-		let req = self
-			.get_new_request(logger_state_uri)
-			.body(Body::empty())
-			.map_crate_err()?;
-
-		let response: Response<Body> = client.request(req).await.map_crate_err()?;
+	pub fn on_caught_up<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_caught_up = Some(Box::new(callback));
+		self
+	}
 
-		match response.status() {
-			StatusCode::OK => {
-				let bytes = hyper::body::to_bytes(response.into_body()).await?;
-				let data: LoggerStateData =
-					serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
+	/// Returns whether the [`Trigger`] has already caught up with the server log at least once
+	/// since it was initialized
+	///
+	/// returns: `bool`
+	pub fn is_caught_up(&self) -> bool {
+		self.caught_up
+	}
 
-				self.last_log_tick = data.state.last_log_tick;
+	/// Marks the [`Trigger`] as caught up, calling the [`on_caught_up`] callback the first time
+	///
+	/// [`on_caught_up`]: #method.on_caught_up
+	fn notify_caught_up(&mut self) {
+		if !self.caught_up {
+			self.caught_up = true;
 
-				Ok(())
+			if let Some(callback) = &self.on_caught_up {
+				callback();
 			}
-			s => Err(s.into()),
 		}
 	}
 
-	/// Listens to the ArangoDB Replication API calling to **`GET /_api/replication/logger-state`**
-	/// giving a query variable `from` the value of the last log tick stored on the [`Trigger`]
-	/// instance. This method doesn't keep listening to the server, but rather it returns whenever
-	/// the response of the HTTP request is already processed. To Keep this process running use this
-	/// method inside a loop and for multi-threading create a thread wrapping the loop, mind that
-	/// there shouldn't be any problems with [`HandlerContext`] data as they're [`std::sync::Arc`]
-	/// wrappers.
+	/// Adds hosts the [`Trigger`] fails over to when the active one can't be reached, e.g. the
+	/// other Coordinators of a cluster. On connection errors the requests rotate to the next host
+	/// right away, trying every host once before falling back to the [`RetryPolicy`] if any. The
+	/// last log tick is kept across failovers
+	///
+	/// # Arguments
+	///
+	/// * `hosts`: The failover hosts, tried in the given order after the main one
+	///
+	/// returns: [`Trigger`]
 	///
 	/// # Examples
+	///
 	/// ```
 	/// use arangodb_events_rs::Trigger;
 	///
 	/// let mut trigger = Trigger::new(
-	///    	"http://localhost:8529/",
-	///    	"alchemy",
-	///	);
+	///     "http://coordinator-1:8529",
+	///     "alchemy",
+	/// )
+	/// .with_failover_hosts(&["http://coordinator-2:8529", "http://coordinator-3:8529"]);
+	/// ```
+	pub fn with_failover_hosts(self, hosts: &[&str]) -> Self {
+		self.hosts
+			.write()
+			.unwrap()
+			.extend(hosts.iter().map(|h| h.to_string()));
+		self
+	}
+
+	/// Enables or disables the leader discovery for Active Failover deployments. When enabled the
+	/// [`Trigger`] asks the known hosts for the current leader calling
+	/// **`GET /_api/cluster/endpoints`** on [`init`] and whenever a server responds
+	/// `503 Service Unavailable`, as followers do, switching to the leader instead of failing.
+	/// Leaders not given as hosts are added to them. Disabled by default
 	///
-	/// trigger.init().await.expect("Error initializing ArangoDB event trigger");
+	/// [`init`]: #method.init
+	///
+	/// # Arguments
+	///
+	/// * `enabled`: Whether to discover the leader
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
 	///
-	/// loop {
-	/// 	// Note that as this is a user-controlled loop, this whole listening process can be
-	/// 	// interrupted any time doing instead of a loop a while or any other systems
-	/// 	trigger.listen().await.unwrap();
-	/// }
 	/// ```
-	pub async fn listen(&mut self) -> Result<()> {
-		let current_tick = self.last_log_tick.clone();
-
-		let client = Client::new();
-
-		let logger_state_uri = self.get_uri(
-			format!(
-				"/_api/replication/logger-follow?from={}",
-				current_tick.as_str()
-			)
-			.as_str(),
-		)?;
-
-		let req = self
-			.get_new_request(logger_state_uri)
-			.body(Body::empty())
-			.map_crate_err()?;
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://arangodb-1:8529",
+	///     "alchemy",
+	/// )
+	/// .with_failover_hosts(&["http://arangodb-2:8529"])
+	/// .with_leader_discovery(true);
+	/// ```
+	pub fn with_leader_discovery(mut self, enabled: bool) -> Self {
+		self.leader_discovery = enabled;
+		self
+	}
 
-		let response: Response<Body> = client.request(req).await.map_crate_err()?;
+	/// Returns the host the requests are currently sent to
+	///
+	/// returns: [`String`]
+	pub fn active_host(&self) -> String {
+		self.hosts.read().unwrap()[self.active_host.load(Ordering::Relaxed)].clone()
+	}
 
-		match response.status() {
-			StatusCode::OK | StatusCode::NO_CONTENT => {
-				let next_log_tick = if let Some(v) = response.headers().get(LAST_LOG_HEADER) {
-					let value = v.to_str().map_crate_err()?;
+	/// Rotates to the next host, returning it
+	fn failover(&self) -> String {
+		let hosts = self.hosts.read().unwrap();
+		let next = (self.active_host.load(Ordering::Relaxed) + 1) % hosts.len();
+		self.active_host.store(next, Ordering::Relaxed);
 
-					if value == "0" {
-						tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+		hosts[next].clone()
+	}
 
-						return Ok(());
-					} else {
-						value
-					}
-				} else {
-					current_tick.as_str()
-				};
+	/// Asks the known hosts for the current Active Failover leader calling
+	/// **`GET /_api/cluster/endpoints`** and makes it the active host
+	///
+	/// returns: `bool`, whether the active host changed
+	async fn discover_leader(&self, client: &Client<Connector>) -> bool {
+		let hosts = self.hosts.read().unwrap().clone();
+		let active = self.active_host.load(Ordering::Relaxed);
 
-				self.last_log_tick = next_log_tick.to_string();
+		for host in hosts.iter().cycle().skip(active).take(hosts.len()) {
+			let leader = match self.get_leader(client, host).await {
+				Ok(Some(leader)) => leader,
+				_ => continue,
+			};
 
-				// If there's no change on tick value, call again process_log_tick
-				if !next_log_tick.eq(&current_tick) {
-					let mut deserializer = Deserializer::new(response.into_body());
+			let mut known = self.hosts.write().unwrap();
 
-					while let Some(line) = deserializer.read_line().await? {
-						self.process_line(line).await?;
-					}
+			let idx = match known.iter().position(|h| *h == leader) {
+				Some(idx) => idx,
+				None => {
+					known.push(leader.clone());
+					known.len() - 1
 				}
+			};
 
-				Ok(())
+			if idx == self.active_host.swap(idx, Ordering::Relaxed) {
+				return false;
 			}
-			s => Err(s.into()),
-		}
-	}
-
-	/// Processes one logger line
-	async fn process_line(&mut self, line: String) -> Result<()> {
-		// We do this kind of parsing with indexes and characters instead of serializing or
-		// deserializing JSON directly using `serde_json` because it'd consume a lot of resources
-		// for some operations that may not be needed to be parsed.
-
-		// Get index after search on line
-		fn find_idx(line: &str, search: &str) -> Result<usize> {
-			Ok(line
-				.find(search)
-				.ok_or(Error::new(Kind::Io(Io::Serialize)))?
-				+ search.len())
-		}
-
-		let type_idx = find_idx(line.as_str(), "\"type\":")?;
 
-		let log_type_str: u16 = utils::get_string_between(line.as_str(), type_idx, 4)
-			.parse()
-			.map_crate_err()?;
+			drop(known);
 
-		// Get transaction id
-		fn get_tid(line: &str) -> Result<String> {
-			let tid_idx = find_idx(line, "\"tid\":\"")?;
+			self.observer.report(
+				Severity::Warning,
+				DiagnosticCategory::Transport,
+				format!("switching to Active Failover leader {}", leader),
+			);
 
-			Ok(utils::get_string_until(line, tid_idx, '"'))
+			return true;
 		}
 
-		if let Ok(log_type) = log_type_str.try_into() {
-			match log_type {
-				LogType::StartTransaction => {
-					let tid = get_tid(line.as_str())?;
-
-					self.transactions.insert(tid.clone(), Transaction::empty());
-				}
-				LogType::RemoveDocument | LogType::InsertOrReplaceDocument => {
-					let tid = get_tid(line.as_str())?;
-
-					fn create_operation(
-						line: &str,
-						log_type: LogType,
-					) -> Result<TransactionOperation> {
-						Ok(if matches!(log_type, LogType::RemoveDocument) {
-							TransactionOperation::RemoveDocument(
-								serde_json::from_str(line).map_crate_err()?,
-							)
-						} else {
-							TransactionOperation::InsertOrReplaceDocument(
-								serde_json::from_str(line).map_crate_err()?,
-							)
-						})
-					}
-
-					// The field tid might contain the value “0” to identify a single operation
-					// that is not part of a multi-document transaction
-					if tid == "0" {
-						let single_op = create_operation(line.as_str(), log_type)?;
+		false
+	}
 
-						self.execute_operation(&single_op).await;
-					} else {
-						// If the transaction's id is not 0 and it's not on already started
-						// transactions we just ignore the operation as it shouldn't get parsed
-						if let Some(t) = self.transactions.get_mut(tid.as_str()) {
-							t.operations
-								.push(create_operation(line.as_str(), log_type)?)
-						}
-					}
-				}
-				LogType::CommitTransaction => {
-					let tid = get_tid(line.as_str())?;
+	/// Calls **`GET /_api/cluster/endpoints`** on the given host, returning the leader host
+	async fn get_leader(&self, client: &Client<Connector>, host: &str) -> Result<Option<String>> {
+		let uri: Uri = format!("{}/_api/cluster/endpoints", host)
+			.parse()
+			.map_err(|e: hyper::http::uri::InvalidUri| -> Error { e.into() })?;
 
-					if let Some(t) = self.transactions.get(tid.as_str()) {
-						for operation in &t.operations {
-							self.execute_operation(operation).await
-						}
-					}
-				}
-				LogType::AbortTransaction => {
-					let tid = get_tid(line.as_str())?;
+		let req = self
+			.get_new_request(uri)
+			.body(Body::empty())
+			.map_crate_err()?;
 
-					self.transactions.remove(tid.as_str());
-				}
-				_ => {}
-			}
-		}
+		let response = self.request(client, req).await?;
 
-		Ok(())
-	}
+		match response.status() {
+			StatusCode::OK => {
+				let bytes = hyper::body::to_bytes(response.into_body()).await?;
+				let data: ClusterEndpoints =
+					serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
 
-	/// Executes a [`TransactionOperation`]
-	async fn execute_operation(&self, op: &TransactionOperation) {
-		match op {
-			TransactionOperation::InsertOrReplaceDocument(ref doc) => {
-				self.subscriptions
-					.call(
-						HandlerEvent::InsertOrReplace,
-						doc,
-						Some(doc.collection.as_str()),
-					)
-					.await
-			}
-			TransactionOperation::RemoveDocument(ref doc) => {
-				self.subscriptions
-					.call(HandlerEvent::Remove, doc, Some(doc.collection.as_str()))
-					.await
+				Ok(data.endpoints.first().and_then(|e| e.host()))
 			}
+			_ => Err(response_error(response).await),
 		}
 	}
 
-	/// Subscribes [`Handler`] to a [`HandlerEvent`] with a given [`HandlerContext`]
+	/// Sets the [`TriggerProxy`] the requests to the ArangoDB server are sent through, e.g. to
+	/// traverse a corporate proxy. Use [`TriggerProxy::from_env`] to honor the `HTTP_PROXY` and
+	/// `HTTPS_PROXY` environment variables
 	///
 	/// # Arguments
 	///
-	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
-	/// * `ctx`: The [`Handler::Context`]. Note that you could pass here any [`HandlerContext`]
-	/// with any type, but note that if its type it's not the same as the [`Handler::Context`] one
-	/// the [`Handler::call`] method is never gonna be called as downcasting will fail
+	/// * `proxy`: The [`TriggerProxy`]
+	///
+	/// returns: [`Trigger`]
 	///
 	/// # Examples
+	///
 	/// ```
-	/// use arangodb_events_rs::api::DocumentOperation;
-	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	/// use arangodb_events_rs::{Trigger, TriggerProxy};
 	///
-	/// pub struct ExampleHandler;
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_proxy(TriggerProxy::from_env().expect("Invalid proxy environment variables"));
+	/// ```
+	pub fn with_proxy(mut self, proxy: TriggerProxy) -> Self {
+		self.proxy = Some(proxy);
+		self.client = OnceLock::new();
+		self
+	}
+
+	/// Enables HTTP/2 on the connections to the ArangoDB server, reducing connection churn and
+	/// head-of-line blocking when polling at high frequency. With `https://` hosts HTTP/2 is
+	/// negotiated through ALPN, falling back to HTTP/1.1 if the server doesn't support it. With
+	/// `http://` hosts it's used with prior knowledge, so the server must support it.
 	///
-	/// pub struct MyContext {
-	///     pub data: u8,
-	/// }
+	/// # Arguments
+	///
+	/// * `enabled`: Whether HTTP/2 should be used
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_http2(true);
+	/// ```
+	pub fn with_http2(mut self, enabled: bool) -> Self {
+		self.http2 = enabled;
+		self.client = OnceLock::new();
+		self
+	}
+
+	/// Sets the maximum time to wait while connecting to the ArangoDB server, or to the proxy if
+	/// any. By default it waits for as long as the operating system allows
+	///
+	/// # Arguments
+	///
+	/// * `timeout`: The connect timeout
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_connect_timeout(Duration::from_secs(5));
+	/// ```
+	pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+		self.connect_timeout = Some(timeout);
+		self.client = OnceLock::new();
+		self
+	}
+
+	/// Sets the [`TriggerHttpConfig`] tuning the connection pool of the HTTP client
+	///
+	/// # Arguments
+	///
+	/// * `config`: The [`TriggerHttpConfig`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{Trigger, TriggerHttpConfig};
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_http_config(TriggerHttpConfig::new().with_pool_max_idle_per_host(1));
+	/// ```
+	pub fn with_http_config(mut self, config: TriggerHttpConfig) -> Self {
+		self.http = config;
+		self.client = OnceLock::new();
+		self
+	}
+
+	/// Sets the maximum time to wait for data from the ArangoDB server, both for the response
+	/// headers of each request and for each line of a response body, so a stalled server doesn't
+	/// stall [`listen`] forever. Timed out requests are retried following the [`RetryPolicy`] if
+	/// any. By default there's no timeout
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `timeout`: The read timeout
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_read_timeout(Duration::from_secs(30));
+	/// ```
+	pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+		self.read_timeout = Some(timeout);
+		self
+	}
+
+	/// Enables re-initializing the [`Trigger`] when its database gets dropped and recreated, as
+	/// usually happens on staging environments. While the database doesn't exist [`listen`] keeps
+	/// waiting for it instead of failing. Once it's back, the [`Trigger`] starts over from the
+	/// server last tick, dispatches a [`HandlerEvent::CollectionReset`] for every collection with
+	/// subscriptions and calls the [`on_database_reset`] callback.
+	///
+	/// [`listen`]: #method.listen
+	/// [`on_database_reset`]: #method.on_database_reset
+	///
+	/// # Arguments
+	///
+	/// * `enabled`: Whether the [`Trigger`] should re-initialize when its database is recreated
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_auto_reinit(true);
+	/// ```
+	pub fn with_auto_reinit(mut self, enabled: bool) -> Self {
+		self.auto_reinit = enabled;
+		self
+	}
+
+	/// Sets a callback invoked each time the [`Trigger`] re-initializes after its database got
+	/// recreated, see [`with_auto_reinit`]. This is the place to re-run any bootstrap of the
+	/// state derived from the database.
+	///
+	/// [`with_auto_reinit`]: #method.with_auto_reinit
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called after each re-initialization
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_auto_reinit(true)
+	/// .on_database_reset(|| println!("Database recreated, rebuilding caches"));
+	/// ```
+	pub fn on_database_reset<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_database_reset = Some(Box::new(callback));
+		self
+	}
+
+	/// Sets the [`LagThreshold`] beyond which the [`Trigger`] health flips to
+	/// [`TriggerHealth::Degraded`] and the [`on_lag_alert`] callback gets called. Health goes back
+	/// to [`TriggerHealth::Healthy`] once the lag is under the threshold again.
+	///
+	/// [`on_lag_alert`]: #method.on_lag_alert
+	///
+	/// # Arguments
+	///
+	/// * `threshold`: The [`LagThreshold`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{LagThreshold, Trigger};
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_lag_threshold(LagThreshold::Time(Duration::from_secs(60)))
+	/// .on_lag_alert(|lag| println!("Trigger is {:?} behind", lag.behind_for));
+	/// ```
+	pub fn with_lag_threshold(mut self, threshold: LagThreshold) -> Self {
+		self.lag_threshold = Some(threshold);
+		self
+	}
+
+	/// Sets a callback invoked each time the [`Trigger`] lag goes beyond the configured
+	/// [`LagThreshold`], see [`with_lag_threshold`]
+	///
+	/// [`with_lag_threshold`]: #method.with_lag_threshold
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with the current [`TriggerLag`]
+	///
+	/// returns: [`Trigger`]
+	pub fn on_lag_alert<F: Fn(&TriggerLag) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_lag_alert = Some(Box::new(callback));
+		self
+	}
+
+	/// Watches a collection for silence, calling the given callback once it receives no events for
+	/// longer than `window`, e.g. to detect upstream writers that silently stopped. The callback
+	/// is called once per silent period, after the `listen()` call that detects it, with the time
+	/// elapsed since the last event. The period starts when the watchdog is set, and the watchdog
+	/// follows collection renames like subscriptions do
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The ArangoDB collection name
+	/// * `window`: The maximum time without events
+	/// * `callback`: The function to be called with the collection name and the silence duration
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_collection_silence("orders", Duration::from_secs(300), |collection, silent_for| {
+	///     println!("no {} events for {:?}", collection, silent_for);
+	/// });
+	/// ```
+	pub fn on_collection_silence<F: Fn(&str, Duration) + Send + Sync + 'static>(
+		mut self,
+		collection: &str,
+		window: Duration,
+		callback: F,
+	) -> Self {
+		self.silence_watchdogs.push(SilenceWatchdog {
+			collection: collection.to_string(),
+			window,
+			last_event: Instant::now(),
+			alerted: false,
+			callback: Box::new(callback),
+		});
+		self
+	}
+
+	/// Calls the callback of every watchdog whose collection went silent for longer than its
+	/// window, once per silent period
+	fn check_silence(&mut self) {
+		for watchdog in &mut self.silence_watchdogs {
+			let silent_for = watchdog.last_event.elapsed();
+
+			if !watchdog.alerted && silent_for > watchdog.window {
+				watchdog.alerted = true;
+				(watchdog.callback)(&watchdog.collection, silent_for);
+			}
+		}
+	}
+
+	/// Returns the [`TriggerLag`] measured on the last `listen()` call
+	///
+	/// returns: `&TriggerLag`
+	pub fn lag(&self) -> &TriggerLag {
+		&self.lag
+	}
+
+	/// Returns the current [`TriggerHealth`]
+	///
+	/// returns: [`TriggerHealth`]
+	pub fn health(&self) -> TriggerHealth {
+		self.health
+	}
+
+	/// Updates the [`TriggerLag`] and [`TriggerHealth`] after a `listen()` call, calling the
+	/// [`on_lag_alert`] callback if the lag went beyond the [`LagThreshold`]
+	///
+	/// [`on_lag_alert`]: #method.on_lag_alert
+	fn update_lag(&mut self, server_tick: Option<u64>, check_more: bool) {
+		let last_tick: u64 = self.last_log_tick.parse().unwrap_or(0);
+
+		if check_more {
+			self.behind_since.get_or_insert_with(Instant::now);
+		} else {
+			self.behind_since = None;
+		}
+
+		self.lag = TriggerLag {
+			ticks: server_tick
+				.map(|t| t.saturating_sub(last_tick))
+				.unwrap_or(0),
+			behind_for: self.behind_since.map(|t| t.elapsed()).unwrap_or_default(),
+		};
+
+		let exceeded = self
+			.lag_threshold
+			.map(|t| t.is_exceeded_by(&self.lag))
+			.unwrap_or(false);
+
+		match self.health {
+			TriggerHealth::Healthy if exceeded => {
+				self.health = TriggerHealth::Degraded;
+
+				if let Some(callback) = &self.on_lag_alert {
+					callback(&self.lag);
+				}
+			}
+			TriggerHealth::Degraded if !exceeded => self.health = TriggerHealth::Healthy,
+			_ => {}
+		}
+
+		#[cfg(feature = "metrics")]
+		self.subscriptions
+			.metrics
+			.record_health(self.health, self.lag.clone());
+
+		self.track_catch_up(server_tick, check_more);
+	}
+
+	/// Enables or disables collection rename tracking. When enabled (the default), collection
+	/// subscriptions are re-pointed to the new collection name whenever a
+	/// [`RenameCollection`](`crate::api::LogType::RenameCollection`) event is received, so they
+	/// keep receiving events after a rename.
+	///
+	/// # Arguments
+	///
+	/// * `enabled`: Whether collection renames should be tracked
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_rename_tracking(false);
+	/// ```
+	pub fn with_rename_tracking(mut self, enabled: bool) -> Self {
+		self.track_renames = enabled;
+		self
+	}
+
+	/// Sets the [`DuplicatePolicy`] applied when the same [`Handler`] gets subscribed twice to the
+	/// same [`HandlerEvent`] and scope. Defaults to [`DuplicatePolicy::Allow`].
+	///
+	/// # Arguments
+	///
+	/// * `policy`: The [`DuplicatePolicy`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{DuplicatePolicy, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_duplicate_policy(DuplicatePolicy::Error);
+	/// ```
+	pub fn with_duplicate_policy(self, policy: DuplicatePolicy) -> Self {
+		self.subscriptions.set_duplicate_policy(policy);
+		self
+	}
+
+	/// Sets a callback invoked after every event dispatch with a [`DispatchReport`] listing which
+	/// handlers were called and which were skipped, and why
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with each [`DispatchReport`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{DispatchOutcome, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_dispatch(|report| {
+	///     for dispatch in &report.handlers {
+	///         if let DispatchOutcome::Skipped(reason) = dispatch.outcome {
+	///             println!("{} skipped on {}: {:?}", dispatch.handler, report.collection, reason);
+	///         }
+	///     }
+	/// });
+	/// ```
+	pub fn on_dispatch<F: Fn(&DispatchReport) + Send + Sync + 'static>(
+		mut self,
+		callback: F,
+	) -> Self {
+		self.on_dispatch = Some(Box::new(callback));
+		self
+	}
+
+	/// Sets a callback receiving the value returned by each [`OutputHandler`] call as a
+	/// [`HandlerResult`], e.g. to forward it to a channel processing the results elsewhere.
+	/// Results are dropped if no callback is set.
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The callback
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let (tx, rx) = std::sync::mpsc::channel();
+	/// let tx = std::sync::Mutex::new(tx);
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_result(move |result| {
+	///     if let Ok(count) = result.downcast::<u64>() {
+	///         tx.lock().unwrap().send(count).unwrap();
+	///     }
+	/// });
+	/// ```
+	pub fn on_result<F: Fn(HandlerResult) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_result = Some(Box::new(callback));
+		self
+	}
+
+	/// Sets the [`DispatchMode`] used to execute the document operations of each
+	/// [`Trigger::listen`] batch. Defaults to [`DispatchMode::Serial`].
+	///
+	/// # Arguments
+	///
+	/// * `mode`: The [`DispatchMode`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{DispatchMode, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_dispatch_mode(DispatchMode::PerCollection);
+	/// ```
+	pub fn with_dispatch_mode(mut self, mode: DispatchMode) -> Self {
+		self.dispatch_mode = mode;
+		self
+	}
+
+	/// Sets a callback invoked with the raw HTTP Headers of every
+	/// **`GET /_api/replication/logger-follow`** or **`GET /_api/wal/tail`** response, following
+	/// the [`LogApi`] in use, before its body gets processed. This
+	/// gives access to replication headers like `X-Arango-Replication-Active`,
+	/// `X-Arango-Replication-Lasttick` or `X-Arango-Replication-Lastscanned` to implement custom
+	/// catch-up heuristics.
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with the response [`HeaderMap`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_response_headers(|headers| {
+	///     if let Some(active) = headers.get("X-Arango-Replication-Active") {
+	///         println!("Replication active: {:?}", active);
+	///     }
+	/// });
+	/// ```
+	pub fn on_response_headers<F: Fn(&HeaderMap) + Send + Sync + 'static>(
+		mut self,
+		callback: F,
+	) -> Self {
+		self.on_response_headers = Some(Box::new(callback));
+		self
+	}
+
+	/// Sets a callback invoked with every [`Event`] received from the replication log in dispatch
+	/// order, including DDL operations and [`Event::Heartbeat`]s which are not dispatched to
+	/// handlers. Document operations of a transaction are only received once it gets committed.
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with each [`Event`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::api::Event;
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_event(|event| {
+	///     if let Event::Collection(op) = event {
+	///         println!("{:?} collection {:?}", op.kind, op.collection);
+	///     }
+	/// });
+	/// ```
+	pub fn on_event<F: Fn(&Event) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_event = Some(Box::new(callback));
+		self
+	}
+
+	/// Sets a callback invoked with every change of the `_users` system collection decoded into a
+	/// [`UserEvent`], so permission changes can be audited as they happen. Users only live on the
+	/// `_system` database, so the Trigger must listen to it. Document operations on `_users` are
+	/// still dispatched to the handlers subscribed to it
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with each [`UserEvent`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "_system",
+	/// )
+	/// .on_user_event(|event| {
+	///     for grant in event.granted() {
+	///         println!(
+	///             "user {} granted {:?} on {}",
+	///             event.key(),
+	///             grant.permissions,
+	///             grant.database
+	///         );
+	///     }
+	/// });
+	/// ```
+	pub fn on_user_event<F: Fn(&UserEvent) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_user_event = Some(Box::new(callback));
+		self
+	}
+
+	/// Sets a callback invoked with every [`Diagnostic`] the [`Trigger`] reports, each one with a
+	/// [`Severity`] and a [`DiagnosticCategory`] so errors can be alerted on while warnings are
+	/// merely logged. Every error returned by [`listen`] is also reported. Without a callback,
+	/// diagnostics are printed to the standard output.
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with each [`Diagnostic`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{Severity, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_diagnostic(|diagnostic| match diagnostic.severity {
+	///     Severity::Warning => println!("{}", diagnostic),
+	///     Severity::Error => eprintln!("ALERT {:?}: {}", diagnostic.category, diagnostic.message),
+	/// });
+	/// ```
+	pub fn on_diagnostic<F: Fn(&Diagnostic) + Send + Sync + 'static>(
+		mut self,
+		callback: F,
+	) -> Self {
+		self.observer.set(callback);
+		self.subscriptions.set_observer(self.observer.clone());
+		self
+	}
+
+	/// Sets a function extracting a deadline from each document operation, e.g. from an
+	/// `expiresAt` field, and the [`StalePolicy`] applied to the operations whose deadline has
+	/// already passed when they get dispatched. Operations without a deadline are never stale.
+	///
+	/// # Arguments
+	///
+	/// * `extractor`: The function returning the document operation deadline, if any
+	/// * `policy`: The [`StalePolicy`] for stale operations
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{StalePolicy, Trigger};
+	/// use std::time::{Duration, SystemTime};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_deadline(
+	///     |doc| {
+	///         doc.field("expiresAt")
+	///             .and_then(|v| v.as_u64())
+	///             .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+	///     },
+	///     StalePolicy::Skip,
+	/// );
+	/// ```
+	pub fn with_deadline<F>(mut self, extractor: F, policy: StalePolicy) -> Self
+	where
+		F: Fn(&DocumentOperation) -> Option<SystemTime> + Send + Sync + 'static,
+	{
+		self.deadline = Some((Box::new(extractor), policy));
+		self
+	}
+
+	/// Sets the [`RetryPolicy`] used to retry the requests to the ArangoDB server on transient
+	/// failures, i.e. connection errors and `5xx` HTTP responses. By default requests are not
+	/// retried and failures are returned straight away by [`init`] and [`listen`].
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `policy`: The [`RetryPolicy`], e.g. a [`Backoff`](`crate::Backoff`)
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{Backoff, Trigger};
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_retry_policy(Backoff::new(Duration::from_millis(200), Duration::from_secs(10)));
+	/// ```
+	pub fn with_retry_policy<P: RetryPolicy + 'static>(mut self, policy: P) -> Self {
+		self.retry = Some(Box::new(policy));
+		self
+	}
+
+	/// Sets a [`PollScheduler`] shared with other [`Trigger`]s, so that each [`listen`] call
+	/// waits for its turn before polling the server log and holds it until its batch has been
+	/// dispatched
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `scheduler`: The shared [`PollScheduler`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{PollScheduler, Trigger};
+	///
+	/// let scheduler = PollScheduler::new(1);
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_poll_scheduler(scheduler.clone());
+	/// ```
+	pub fn with_poll_scheduler(mut self, scheduler: PollScheduler) -> Self {
+		self.scheduler = Some(scheduler);
+		self
+	}
+
+	/// Enables or disables dry-run mode. In dry-run mode the [`Trigger`] keeps polling, parsing
+	/// and ordering events as usual but never calls any [`Handler`], which get reported as
+	/// [`SkipReason::DryRun`] on the [`DispatchReport`]s instead. Use it along with
+	/// [`on_dispatch`] to validate a new subscription setup against production traffic.
+	///
+	/// [`on_dispatch`]: #method.on_dispatch
+	///
+	/// # Arguments
+	///
+	/// * `enabled`: Whether handlers should be skipped
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_dry_run(true)
+	/// .on_dispatch(|report| {
+	///     for dispatch in &report.handlers {
+	///         println!("would call {} on {}", dispatch.handler, report.collection);
+	///     }
+	/// });
+	/// ```
+	pub fn with_dry_run(mut self, enabled: bool) -> Self {
+		self.dry_run = enabled;
+		self
+	}
+
+	/// Attaches an HTTP header to every request sent to the ArangoDB server, e.g. API keys or
+	/// tenant headers required by a reverse proxy in front of it. Calling it several times with the
+	/// same name sends all the values
+	///
+	/// # Arguments
+	///
+	/// * `name`: The header name
+	/// * `value`: The header value
+	///
+	/// returns: `Result<Trigger>`, failing if the name or value aren't valid HTTP header ones
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_header("X-Api-Key", "secret")
+	/// .expect("Invalid HTTP header");
+	/// ```
+	pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+		let name = HeaderName::from_bytes(name.as_bytes())?;
+		let value = HeaderValue::from_str(value)?;
+
+		self.headers.append(name, value);
+
+		Ok(self)
+	}
+
+	/// Caps the number of operations dispatched on each [`listen`] call, independently of the
+	/// response size. Once the cap is reached the rest of the response is discarded and fetched
+	/// again by the next call, so memory usage and per-call latency stay bounded while catching
+	/// up. Operations of a transaction are counted once it gets committed.
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `max`: The maximum number of operations per [`listen`] call
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_max_batch_size(1000);
+	/// ```
+	pub fn with_max_batch_size(mut self, max: usize) -> Self {
+		self.max_batch_size = Some(max);
+		self
+	}
+
+	/// Caps the size of the body read on each [`listen`] call, protecting small consumers from
+	/// huge responses, e.g. when catching up after a long downtime. The `chunkSize` requested to
+	/// the server starts at `max`; when a response goes beyond it anyway, the rest of it is
+	/// discarded and fetched again by the next call with half the `chunkSize`, which grows back on
+	/// each response staying under the cap. Compressed bodies, see the `gzip` feature, are
+	/// measured once decompressed.
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `max`: The maximum body size in bytes
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_max_body_size(64 * 1024 * 1024);
+	/// ```
+	pub fn with_max_body_size(mut self, max: u64) -> Self {
+		self.max_body_size = Some(max);
+		self.chunk_size = max;
+		self
+	}
+
+	/// Sets a callback invoked every `every` dispatched events with the cumulative
+	/// [`TriggerProgress`], convenient to log the progress of long catch-up runs. As events are
+	/// counted once their batch is dispatched, the callback may be called with a count a bit
+	/// beyond the exact multiple.
+	///
+	/// # Arguments
+	///
+	/// * `every`: The number of events between two calls
+	/// * `callback`: The function to be called with the [`TriggerProgress`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_progress(10_000, |progress| {
+	///     println!("{} events processed, at tick {}", progress.events, progress.tick);
+	/// });
+	/// ```
+	pub fn on_progress<F: Fn(&TriggerProgress) + Send + Sync + 'static>(
+		mut self,
+		every: u64,
+		callback: F,
+	) -> Self {
+		self.on_progress = Some((every.max(1), Box::new(callback)));
+		self
+	}
+
+	/// Sets a callback invoked every `interval` with the [`CatchUpProgress`] while the [`Trigger`]
+	/// is behind the server log, e.g. to display the sync progress to end users. The catch-up
+	/// starts when a [`listen`] call finds more log entries pending and ends once it reaches the
+	/// end of the log, see [`on_caught_up`]
+	///
+	/// [`listen`]: #method.listen
+	/// [`on_caught_up`]: #method.on_caught_up
+	///
+	/// # Arguments
+	///
+	/// * `interval`: The minimum time between two calls
+	/// * `callback`: The function to be called with the [`CatchUpProgress`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_catch_up_progress(Duration::from_secs(5), |progress| {
+	///     println!(
+	///         "{} events synced, ~{} left ({:?})",
+	///         progress.processed, progress.remaining_estimate, progress.eta
+	///     );
+	/// });
+	/// ```
+	pub fn on_catch_up_progress<F: Fn(&CatchUpProgress) + Send + Sync + 'static>(
+		mut self,
+		interval: Duration,
+		callback: F,
+	) -> Self {
+		self.on_catch_up = Some((interval, Box::new(callback)));
+		self
+	}
+
+	/// Updates the [`CatchUp`] estimates after a `listen()` call, calling the
+	/// [`on_catch_up_progress`] callback if its interval elapsed
+	///
+	/// [`on_catch_up_progress`]: #method.on_catch_up_progress
+	fn track_catch_up(&mut self, server_tick: Option<u64>, check_more: bool) {
+		let (interval, callback) = match &self.on_catch_up {
+			Some(on_catch_up) => on_catch_up,
+			None => return,
+		};
+
+		if !check_more {
+			self.catch_up = None;
+			return;
+		}
+
+		let tick: u64 = self.last_log_tick.parse().unwrap_or(0);
+		let now = Instant::now();
+
+		let catch_up = self.catch_up.get_or_insert(CatchUp {
+			tick,
+			events: self.dispatched,
+			sample: (now, tick),
+			rate: None,
+			reported: now,
+		});
+
+		let elapsed = now.duration_since(catch_up.sample.0).as_secs_f64();
+
+		if elapsed > 0.0 && tick > catch_up.sample.1 {
+			let rate = (tick - catch_up.sample.1) as f64 / elapsed;
+
+			catch_up.rate = Some(match catch_up.rate {
+				Some(average) => average + CATCH_UP_RATE_WEIGHT * (rate - average),
+				None => rate,
+			});
+			catch_up.sample = (now, tick);
+		}
+
+		if now.duration_since(catch_up.reported) < *interval {
+			return;
+		}
+
+		catch_up.reported = now;
+
+		let processed = self.dispatched - catch_up.events;
+		let remaining_ticks = server_tick
+			.map(|t| t.saturating_sub(tick))
+			.unwrap_or(self.lag.ticks);
+
+		let events_per_tick = if processed > 0 && tick > catch_up.tick {
+			processed as f64 / (tick - catch_up.tick) as f64
+		} else {
+			self.events_per_tick
+		};
+
+		callback(&CatchUpProgress {
+			processed,
+			remaining_estimate: (remaining_ticks as f64 * events_per_tick).round() as u64,
+			eta: catch_up
+				.rate
+				.filter(|r| *r > 0.0)
+				.map(|r| Duration::from_secs_f64(remaining_ticks as f64 / r)),
+		});
+	}
+
+	/// Adds the given number of events to the dispatched count, calling the [`on_progress`]
+	/// callback if a multiple of its interval got crossed
+	///
+	/// [`on_progress`]: #method.on_progress
+	fn track_progress(&mut self, events: usize) {
+		let previous = self.dispatched;
+		self.dispatched += events as u64;
+
+		if let Some((every, callback)) = &self.on_progress {
+			if self.dispatched / every > previous / every {
+				callback(&TriggerProgress {
+					events: self.dispatched,
+					tick: self.last_log_tick.parse().unwrap_or(0),
+				});
+			}
+		}
+	}
+
+	/// Sets a callback invoked after each [`listen`] call with its [`PollStats`], see
+	/// [`last_poll`]
+	///
+	/// [`listen`]: #method.listen
+	/// [`last_poll`]: #method.last_poll
+	///
+	/// # Arguments
+	///
+	/// * `callback`: The function to be called with the [`PollStats`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_poll(|stats| {
+	///     println!(
+	///         "{} bytes and {} events in {:?}",
+	///         stats.body_size, stats.events, stats.duration
+	///     );
+	/// });
+	/// ```
+	pub fn on_poll<F: Fn(&PollStats) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+		self.on_poll = Some(Box::new(callback));
+		self
+	}
+
+	/// Returns the [`PollStats`] of the last successful [`listen`] call
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// returns: `&PollStats`
+	pub fn last_poll(&self) -> &PollStats {
+		&self.last_poll
+	}
+
+	/// Stores the [`PollStats`] of a [`listen`] call, calling the [`on_poll`] callback
+	///
+	/// [`listen`]: #method.listen
+	/// [`on_poll`]: #method.on_poll
+	fn record_poll(&mut self, stats: PollStats) {
+		if let Some(callback) = &self.on_poll {
+			callback(&stats);
+		}
+
+		self.last_poll = stats;
+	}
+
+	/// Sets the [`ResumeToken`] to resume from, so that the next [`init`] call keeps its tick
+	/// instead of starting from the current server tick. [`init`] fails if the token belongs to
+	/// another server.
+	///
+	/// [`init`]: #method.init
+	/// [`resume_token`]: #method.resume_token
+	///
+	/// # Arguments
+	///
+	/// * `token`: The [`ResumeToken`] returned by [`resume_token`] before stopping
+	///
+	/// returns: [`Trigger`]
+	pub fn with_resume_token(mut self, token: ResumeToken) -> Self {
+		self.resume = Some(token);
+		self
+	}
+
+	/// Sets the [`TickStore`] persisting the [`Trigger`] position. [`init`] resumes from the
+	/// [`ResumeToken`] it loads, unless one was given with [`with_resume_token`], and each
+	/// [`listen`] call processing new log entries saves the [`resume_token`] to it once they are
+	/// dispatched. Failing to save makes the [`listen`] call fail, the events being dispatched
+	/// again after a restart. [`FileTickStore`] keeps it on a local file.
+	///
+	/// [`FileTickStore`]: crate::FileTickStore
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	/// [`with_resume_token`]: #method.with_resume_token
+	/// [`resume_token`]: #method.resume_token
+	///
+	/// # Arguments
+	///
+	/// * `store`: The [`TickStore`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{ResumeToken, Result, TickStore, Trigger};
+	///
+	/// struct LogTickStore;
+	///
+	/// impl TickStore for LogTickStore {
+	///     fn load(&self) -> Result<Option<ResumeToken>> {
+	///         Ok(None)
+	///     }
+	///
+	///     fn save(&self, token: &ResumeToken) -> Result<()> {
+	///         println!("processed up to tick {}", token.tick);
+	///         Ok(())
+	///     }
+	/// }
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_tick_store(LogTickStore);
+	/// ```
+	pub fn with_tick_store<S: TickStore + 'static>(mut self, store: S) -> Self {
+		self.tick_store = Some(Box::new(store));
+		self
+	}
+
+	/// Keeps the operations of the transactions open at the tick of the [`resume_token`] on the
+	/// token itself. By default the token points right before the earliest open transaction
+	/// started, so that no buffered operation is lost on restart, at the cost of dispatching
+	/// again the operations in between. With this enabled the token keeps the last processed
+	/// tick and [`init`] restores the open transactions from it, so that nothing is dispatched
+	/// twice, at the cost of a token growing with the size of the open transactions.
+	///
+	/// [`resume_token`]: #method.resume_token
+	/// [`init`]: #method.init
+	///
+	/// # Arguments
+	///
+	/// * `enabled`: Whether open transactions are kept on the [`ResumeToken`], `false` by default
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{FileTickStore, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_tick_store(FileTickStore::new("alchemy.tick"))
+	/// .with_transaction_checkpoint(true);
+	/// ```
+	pub fn with_transaction_checkpoint(mut self, enabled: bool) -> Self {
+		self.checkpoint_transactions = enabled;
+		self
+	}
+
+	/// Saves the [`resume_token`] to the [`TickStore`] if the tick changed since the last save
+	///
+	/// [`resume_token`]: #method.resume_token
+	fn save_tick(&mut self) -> Result<()> {
+		let store = match &self.tick_store {
+			Some(store) => store,
+			None => return Ok(()),
+		};
+
+		let token = self.resume_token();
+
+		if self.saved_tick.as_ref() == Some(&token.tick) {
+			return Ok(());
+		}
+
+		store.save(&token)?;
+		self.saved_tick = Some(token.tick);
+
+		Ok(())
+	}
+
+	/// Makes the [`Trigger`] hold a replication barrier on the server, so the server log is kept
+	/// from the Trigger's current tick on even if the consumer falls behind, instead of being
+	/// pruned. The barrier is created by [`init`], or taken over from the [`ResumeToken`] if it's
+	/// still alive, and extended to the current tick by [`listen`] every half `ttl`. It expires
+	/// on its own `ttl` after the Trigger stops, unless released first with [`release_barrier`].
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	/// [`release_barrier`]: #method.release_barrier
+	///
+	/// # Arguments
+	///
+	/// * `ttl`: The time the server keeps the barrier without being extended
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_replication_barrier(Duration::from_secs(600));
+	/// ```
+	pub fn with_replication_barrier(mut self, ttl: Duration) -> Self {
+		self.barrier_ttl = Some(ttl);
+		self
+	}
+
+	/// Returns the id of the replication barrier held by the [`Trigger`], if any, see
+	/// [`with_replication_barrier`]
+	///
+	/// [`with_replication_barrier`]: #method.with_replication_barrier
+	///
+	/// returns: `Option<&str>`
+	pub fn barrier_id(&self) -> Option<&str> {
+		self.barrier_id.as_deref()
+	}
+
+	/// Removes the replication barrier held by the [`Trigger`] calling
+	/// **`DELETE /_api/replication/barrier/{id}`**, letting the server prune its log again. Call
+	/// it when stopping for good, as the barrier would otherwise be kept until its `ttl` expires.
+	/// The barrier is created again by the next [`init`] call.
+	///
+	/// [`init`]: #method.init
+	///
+	/// returns: `Result<()>`
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_replication_barrier(Duration::from_secs(600));
+	///
+	/// trigger.init().await.unwrap();
+	/// trigger.listen().await.unwrap();
+	///
+	/// trigger.release_barrier().await.unwrap();
+	/// # }
+	/// ```
+	pub async fn release_barrier(&mut self) -> Result<()> {
+		let id = match self.barrier_id.take() {
+			Some(id) => id,
+			None => return Ok(()),
+		};
+
+		self.barrier_extended = None;
+
+		let client = self.client()?;
+		let response = self
+			.barrier_request(&client, Method::DELETE, Some(id.as_str()), None)
+			.await?;
+
+		match response.status() {
+			StatusCode::OK | StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(()),
+			_ => Err(response_error(response).await),
+		}
+	}
+
+	/// Sends a request to the replication barrier API, to the barrier with the given id if any,
+	/// retrying it like any other request, see [`send_request_with`]
+	///
+	/// [`send_request_with`]: #method.send_request_with
+	async fn barrier_request(
+		&self,
+		client: &Client<Connector>,
+		method: Method,
+		id: Option<&str>,
+		body: Option<JsonValue>,
+	) -> Result<Response<Body>> {
+		let endpoint = match id {
+			Some(id) => format!("/_api/replication/barrier/{}", id),
+			None => "/_api/replication/barrier".to_string(),
+		};
+
+		self.send_json(client, method, endpoint.as_str(), body.as_ref())
+			.await
+	}
+
+	/// Checks the server log still holds the entries following the given tick calling
+	/// **`GET /_api/replication/logger-tick-ranges`**, failing with a `TickExpired` [`Error`]
+	/// otherwise. Servers not reporting their tick ranges are not checked, the `fromPresent`
+	/// header of the log responses being checked anyway
+	async fn check_tick_range(&self, client: &Client<Connector>, tick: &str) -> Result<()> {
+		let response = self
+			.send_request(client, "/_api/replication/logger-tick-ranges")
+			.await?;
+
+		if response.status() != StatusCode::OK {
+			return Ok(());
+		}
+
+		let bytes = hyper::body::to_bytes(response.into_body()).await?;
+		let ranges: Vec<TickRange> = serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
+
+		let first = ranges
+			.iter()
+			.filter_map(|r| r.tick_min.parse::<u64>().ok())
+			.filter(|t| *t > 0)
+			.min();
+
+		match (first, tick.parse::<u64>()) {
+			(Some(first), Ok(tick)) if tick.saturating_add(1) < first => Err(Error::new(
+				Kind::ArangoDB(ArangoDBError::TickExpired),
+			)
+			.with(format!(
+				"tick {} is older than the first tick {} held by the server",
+				tick, first
+			))),
+			_ => Ok(()),
+		}
+	}
+
+	/// Extends the replication barrier to the tick of the [`resume_token`] calling
+	/// **`PUT /_api/replication/barrier/{id}`**, or creates it calling
+	/// **`POST /_api/replication/barrier`** if there's none or the server doesn't have it anymore
+	///
+	/// [`resume_token`]: #method.resume_token
+	async fn refresh_barrier(&mut self, client: &Client<Connector>) -> Result<()> {
+		let ttl = match self.barrier_ttl {
+			Some(ttl) => ttl,
+			None => return Ok(()),
+		};
+
+		let body = serde_json::json!({
+			"ttl": ttl.as_secs().max(1),
+			"tick": self.checkpoint_tick(),
+		});
+
+		if let Some(id) = &self.barrier_id {
+			let response = self
+				.barrier_request(client, Method::PUT, Some(id.as_str()), Some(body.clone()))
+				.await?;
+
+			match response.status() {
+				StatusCode::OK | StatusCode::NO_CONTENT => {
+					self.barrier_extended = Some(Instant::now());
+
+					return Ok(());
+				}
+				StatusCode::NOT_FOUND => {
+					self.observer.report(
+						Severity::Warning,
+						DiagnosticCategory::Checkpoint,
+						format!(
+							"replication barrier {} expired, the server log may have been pruned \
+							 since tick {}",
+							id, self.last_log_tick
+						),
+					);
+				}
+				_ => return Err(response_error(response).await),
+			}
+		}
+
+		let response = self
+			.barrier_request(client, Method::POST, None, Some(body))
+			.await?;
+
+		if !matches!(response.status(), StatusCode::OK | StatusCode::CREATED) {
+			return Err(response_error(response).await);
+		}
+
+		let bytes = hyper::body::to_bytes(response.into_body()).await?;
+		let data: BarrierResponse = serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
+
+		self.barrier_id = Some(data.id());
+		self.barrier_extended = Some(Instant::now());
+
+		Ok(())
+	}
+
+	/// Extends the replication barrier if half of its `ttl` has passed since the last extension,
+	/// reporting failures as warnings so that they are retried on the next [`listen`] call
+	///
+	/// [`listen`]: #method.listen
+	async fn maintain_barrier(&mut self) {
+		// Released barriers are only created again by init
+		let due = match (self.barrier_ttl, self.barrier_extended) {
+			(Some(ttl), Some(extended)) => extended.elapsed() >= ttl / 2,
+			_ => false,
+		};
+
+		if !due {
+			return;
+		}
+
+		let result = match self.client() {
+			Ok(client) => self.refresh_barrier(&client).await,
+			Err(e) => Err(e),
+		};
+
+		if let Err(e) = result {
+			self.observer.report(
+				Severity::Warning,
+				e.category(),
+				format!("unable to extend the replication barrier: {}", e),
+			);
+		}
+	}
+
+	/// Sets the path of the document field identifying the documents of a collection, e.g. a
+	/// business identifier like `externalId` or `customer.id`, instead of the `_key`. The key is
+	/// reported on the [`DispatchReport`]s and on the slowest calls of [`stats`]
+	///
+	/// [`stats`]: #method.stats
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The ArangoDB collection name
+	/// * `path`: The dot separated field path, see [`DocumentOperation::field`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_key_path("orders", "externalId")
+	/// .on_dispatch(|report| println!("{} {:?}", report.collection, report.key));
+	/// ```
+	pub fn with_key_path(mut self, collection: &str, path: &str) -> Self {
+		self.key_paths
+			.insert(collection.to_string(), path.to_string());
+		self
+	}
+
+	/// Returns the key of a document following the key path of its collection, see
+	/// [`with_key_path`]. String, number and boolean fields are supported
+	///
+	/// [`with_key_path`]: #method.with_key_path
+	fn document_key(&self, doc: &DocumentOperation) -> Option<String> {
+		let path = self
+			.key_paths
+			.get(doc.collection.as_str())
+			.map(String::as_str)
+			.unwrap_or("_key");
+
+		match doc.field(path)? {
+			JsonValue::String(key) => Some(key.clone()),
+			key @ (JsonValue::Number(_) | JsonValue::Bool(_)) => Some(key.to_string()),
+			_ => None,
+		}
+	}
+
+	/// Sets the [`LogApi`] the [`Trigger`] reads the server log from. Use [`LogApi::WalTail`] for
+	/// ArangoDB 3.8 and later, which no longer serve **`logger-follow`**. Both APIs share the same
+	/// ticks, so a [`ResumeToken`] of one can be resumed with the other
+	///
+	/// # Arguments
+	///
+	/// * `api`: The [`LogApi`], [`LogApi::LoggerFollow`] by default
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{LogApi, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_log_api(LogApi::WalTail);
+	/// ```
+	pub fn with_log_api(mut self, api: LogApi) -> Self {
+		self.log_api = api;
+		self
+	}
+
+	/// Returns the [`LogApi`] the [`Trigger`] reads the server log from, see [`with_log_api`]
+	///
+	/// [`with_log_api`]: #method.with_log_api
+	///
+	/// returns: [`LogApi`]
+	pub fn log_api(&self) -> LogApi {
+		self.log_api
+	}
+
+	/// Returns the [`ServerInfo`] fetched by [`init`], or [`None`] before initializing the
+	/// [`Trigger`] or if the server didn't report it
+	///
+	/// [`init`]: #method.init
+	///
+	/// returns: `Option<&ServerInfo>`
+	pub fn server_info(&self) -> Option<&ServerInfo> {
+		self.server_info.as_ref()
+	}
+
+	/// Calls **`GET /_api/version`** endpoint on the ArangoDB server, returning [`None`] if the
+	/// server doesn't report its version
+	async fn fetch_server_info(&self, client: &Client<Connector>) -> Result<Option<ServerInfo>> {
+		let response = self.send_request(client, "/_api/version").await?;
+
+		if response.status() != StatusCode::OK {
+			return Ok(None);
+		}
+
+		let bytes = hyper::body::to_bytes(response.into_body()).await?;
+
+		Ok(Some(
+			serde_json::from_slice(bytes.as_ref()).map_crate_err()?,
+		))
+	}
+
+	/// Calls **`GET /_api/collection`** endpoint on the ArangoDB server to name the collections
+	/// **`wal/tail`** lines only reference by their `cuid`. Other [`LogApi`]s don't need it
+	async fn fetch_collection_names(
+		&self,
+		client: &Client<Connector>,
+		api: LogApi,
+	) -> Result<CollectionNames> {
+		if api != LogApi::WalTail {
+			return Ok(CollectionNames::default());
+		}
+
+		let response = self.send_request(client, "/_api/collection").await?;
+
+		if response.status() != StatusCode::OK {
+			return Err(response_error(response).await);
+		}
+
+		let bytes = hyper::body::to_bytes(response.into_body()).await?;
+		let data: CollectionsResponse = serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
+
+		Ok(CollectionNames::new(data.result))
+	}
+
+	/// Returns the [`LogApi`] to read the server log from, failing if the given [`ServerInfo`]
+	/// doesn't support it
+	fn resolve_log_api(&self, info: Option<&ServerInfo>) -> Result<LogApi> {
+		if let Some(info) = info {
+			info.require(self.log_api.into())?;
+		}
+
+		Ok(self.log_api)
+	}
+
+	/// Returns a [`ResumeToken`] holding the current position of the [`Trigger`] on the server log.
+	/// While transactions are open their operations are only buffered in memory, so the token
+	/// points right before the earliest of them started instead. Resuming from it reads them
+	/// again, along with the operations dispatched after their start, unless the token keeps them,
+	/// see [`with_transaction_checkpoint`].
+	///
+	/// [`with_transaction_checkpoint`]: #method.with_transaction_checkpoint
+	///
+	/// returns: [`ResumeToken`]
+	pub fn resume_token(&self) -> ResumeToken {
+		ResumeToken {
+			tick: self.checkpoint_tick(),
+			server_id: self.server_id.clone(),
+			barrier_id: self.barrier_id.clone(),
+			api: self.log_api,
+			transactions: if self.checkpoint_transactions {
+				self.log.open_transactions()
+			} else {
+				Vec::new()
+			},
+		}
+	}
+
+	/// Returns the tick of the [`resume_token`], see [`with_transaction_checkpoint`]
+	///
+	/// [`resume_token`]: #method.resume_token
+	/// [`with_transaction_checkpoint`]: #method.with_transaction_checkpoint
+	fn checkpoint_tick(&self) -> String {
+		if self.checkpoint_transactions {
+			self.last_log_tick.clone()
+		} else {
+			self.log.resume_tick(self.last_log_tick.as_str())
+		}
+	}
+
+	/// Sets a [`StartupProbe`] run on the first [`listen`] call after [`init`], checking how much
+	/// the server log advanced in between, e.g. while subscriptions were being set up. Events are
+	/// still fetched from the tick stored by [`init`], but a large advance means a backlog to catch
+	/// up with, or in the worst case log entries the server has already pruned.
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `probe`: The [`StartupProbe`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{StartupProbe, Trigger};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_startup_probe(StartupProbe::Error { max_ticks: 100_000 });
+	/// ```
+	pub fn with_startup_probe(mut self, probe: StartupProbe) -> Self {
+		self.startup_probe = Some(probe);
+		self
+	}
+
+	/// Sets the sliding window of the `window_*` counters of [`TriggerStats`], see [`stats`].
+	/// Defaults to one minute.
+	///
+	/// [`stats`]: #method.stats
+	///
+	/// # Arguments
+	///
+	/// * `window`: The duration of the sliding window
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	/// use std::time::Duration;
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_stats_window(Duration::from_secs(300));
+	/// ```
+	pub fn with_stats_window(mut self, window: std::time::Duration) -> Self {
+		self.stats.set_window(window);
+		self
+	}
+
+	/// Returns the per-collection document operation counters and data size, since the [`Trigger`]
+	/// was created and over a sliding window, along with the per-handler call latencies and slowest
+	/// recent call
+	///
+	/// returns: [`TriggerStats`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// for (collection, stats) in trigger.stats().collections {
+	///     println!("{}: {} events in the last minute", collection, stats.window_events);
+	/// }
+	///
+	/// for (handler, stats) in trigger.stats().handlers {
+	///     println!("{}: p95 {:?}, slowest {:?}", handler, stats.p95, stats.slowest);
+	/// }
+	/// ```
+	pub fn stats(&self) -> TriggerStats {
+		self.stats.snapshot()
+	}
+
+	/// Sets the [`TriggerTlsConfig`] used to connect to `https://` hosts
+	///
+	/// [`TriggerTlsConfig`]: crate::TriggerTlsConfig
+	///
+	/// # Arguments
+	///
+	/// * `config`: The [`TriggerTlsConfig`]
+	///
+	/// returns: [`Trigger`]
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::{Trigger, TriggerTlsConfig};
+	///
+	/// let mut trigger = Trigger::new(
+	///     "https://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .with_tls_config(TriggerTlsConfig::new().danger_accept_invalid_certs(true));
+	/// ```
+	#[cfg(feature = "tls")]
+	pub fn with_tls_config(mut self, config: crate::TriggerTlsConfig) -> Self {
+		self.tls = config;
+		self.client = OnceLock::new();
+		self
+	}
+
+	/// Returns a [`TriggerHandle`] to control the [`Trigger`] from elsewhere, e.g. from a
+	/// [`Handler`] through its context
+	///
+	/// returns: [`TriggerHandle`]
+	pub fn handle(&self) -> TriggerHandle {
+		self.handle.clone()
+	}
+
+	/// Whether the operations of a [`Trigger::listen`] batch need to be buffered before being
+	/// dispatched, instead of dispatching them as soon as they're parsed
+	fn buffers_batches(&self) -> bool {
+		!self.priorities.is_empty()
+			|| self.dispatch_mode == DispatchMode::PerCollection
+			|| matches!(self.deadline, Some((_, StalePolicy::Deprioritize)))
+	}
+
+	/// Whether an [`Event`] is a document operation whose deadline has already passed, see
+	/// [`with_deadline`]
+	///
+	/// [`with_deadline`]: #method.with_deadline
+	fn is_stale(&self, event: &Event) -> bool {
+		match (&self.deadline, event) {
+			(Some((extractor, _)), Event::InsertOrReplace(doc) | Event::Remove(doc)) => {
+				extractor(doc)
+					.map(|deadline| deadline <= SystemTime::now())
+					.unwrap_or(false)
+			}
+			_ => false,
+		}
+	}
+
+	/// Returns the HTTP client used to connect to the ArangoDB server, creating it on the first
+	/// call so its connections are pooled across [`init`] and [`listen`] calls
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	fn client(&self) -> Result<Client<Connector>> {
+		if let Some(client) = self.client.get() {
+			return Ok(client.clone());
+		}
+
+		let client = self.new_client()?;
+
+		Ok(self.client.get_or_init(|| client).clone())
+	}
+
+	/// Creates the HTTP client used to connect to the ArangoDB server through the
+	/// [`TriggerProxy`] if any, using HTTP/2 if enabled. With the `tls` feature enabled it
+	/// supports `https://` hosts following the [`TriggerTlsConfig`]
+	///
+	/// [`TriggerTlsConfig`]: crate::TriggerTlsConfig
+	fn new_client(&self) -> Result<Client<Connector>> {
+		let connector = ProxyConnector::new(
+			self.proxy.clone(),
+			self.connect_timeout,
+			self.http.keepalive,
+		);
+
+		#[cfg(feature = "tls")]
+		let connector = {
+			let builder = hyper_rustls::HttpsConnectorBuilder::new()
+				.with_tls_config(self.tls.build()?)
+				.https_or_http()
+				.enable_http1();
+
+			if self.http2 {
+				builder.enable_http2().wrap_connector(connector)
+			} else {
+				builder.wrap_connector(connector)
+			}
+		};
+
+		let mut builder = Client::builder();
+
+		if let Some(timeout) = self.http.pool_idle_timeout {
+			builder.pool_idle_timeout(timeout);
+		}
+
+		if let Some(max) = self.http.pool_max_idle_per_host {
+			builder.pool_max_idle_per_host(max);
+		}
+
+		// Without TLS there's no protocol negotiation, so HTTP/2 is used with prior knowledge
+		if self.http2
+			&& !self
+				.hosts
+				.read()
+				.unwrap()
+				.iter()
+				.any(|h| h.starts_with("https://"))
+		{
+			builder.http2_only(true);
+		}
+
+		Ok(builder.build(connector))
+	}
+
+	/// Gets HTTP URI for the given endpoint with the active host and database stored
+	fn get_uri(&self, endpoint: &str) -> Result<Uri> {
+		format!("{}/_db/{}{}", self.active_host(), self.database, endpoint)
+			.parse()
+			.map_err(|e: hyper::http::uri::InvalidUri| e.into())
+	}
+
+	/// Retrieves `Authorization` HTTP Header value by a [`TriggerAuthentication`], if there's any
+	fn get_authorization_value(&self, auth: &TriggerAuthentication) -> Option<String> {
+		match &auth.method {
+			AuthenticationMethod::Basic { user, password } => Some(format!(
+				"Basic {}",
+				base64::encode(format!("{}:{}", user, password))
+			)),
+			AuthenticationMethod::Jwt(token) => Some(format!("bearer {}", token)),
+			AuthenticationMethod::JwtLogin { token, .. } => token
+				.lock()
+				.unwrap()
+				.as_ref()
+				.map(|t| format!("bearer {}", t)),
+		}
+	}
+
+	/// Whether the [`Trigger`] authenticates with a JWT it can renew by itself
+	fn renews_jwt(&self) -> bool {
+		matches!(
+			self.auth.as_ref().map(|a| &a.method),
+			Some(AuthenticationMethod::JwtLogin { .. })
+		)
+	}
+
+	/// Logs in to the ArangoDB server calling **`POST /_open/auth`** if the [`Trigger`] uses
+	/// [`TriggerAuthentication::jwt_login`], storing the returned JWT. Unless `renew` is set, it
+	/// does nothing if there's already a JWT
+	async fn login(&self, client: &Client<Connector>, renew: bool) -> Result<()> {
+		let (user, password, token) = match self.auth.as_ref().map(|a| &a.method) {
+			Some(AuthenticationMethod::JwtLogin {
+				user,
+				password,
+				token,
+			}) => (user, password, token),
+			_ => return Ok(()),
+		};
+
+		if !renew && token.lock().unwrap().is_some() {
+			return Ok(());
+		}
+
+		let uri: Uri = format!("{}/_open/auth", self.active_host())
+			.parse()
+			.map_err(|e: hyper::http::uri::InvalidUri| -> Error { e.into() })?;
+
+		let body = serde_json::json!({ "username": user, "password": password });
+
+		let mut req = self.with_proxy_authorization(Request::post(uri));
+
+		if let Some(headers) = req.headers_mut() {
+			headers.extend(self.headers.clone());
+		}
+
+		let req = req
+			.header(hyper::header::CONTENT_TYPE, "application/json")
+			.body(Body::from(body.to_string()))
+			.map_crate_err()?;
+
+		let response = self.request(client, req).await?;
+
+		match response.status() {
+			StatusCode::OK => {
+				let bytes = hyper::body::to_bytes(response.into_body()).await?;
+				let data: AuthResponse = serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
+
+				*token.lock().unwrap() = Some(data.jwt);
+
+				Ok(())
+			}
+			s => Err(s.into()),
+		}
+	}
+
+	/// Adds the `Proxy-Authorization` header to a request forwarded through the [`TriggerProxy`]
+	fn with_proxy_authorization(&self, req: HttpRequestBuilder) -> HttpRequestBuilder {
+		let value = req
+			.uri_ref()
+			.zip(self.proxy.as_ref())
+			.and_then(|(uri, proxy)| proxy.authorization(uri))
+			.map(|v| v.to_string());
+
+		match value {
+			Some(value) => req.header(hyper::header::PROXY_AUTHORIZATION, value),
+			None => req,
+		}
+	}
+
+	/// Creates a [`HttpRequestBuilder`] with the given [`Uri`]
+	fn get_new_request(&self, uri: Uri) -> HttpRequestBuilder {
+		let mut req = self.with_proxy_authorization(Request::builder().uri(uri));
+
+		if let Some(headers) = req.headers_mut() {
+			headers.extend(self.headers.clone());
+		}
+
+		if let Some(value) = self
+			.auth
+			.as_ref()
+			.and_then(|a| self.get_authorization_value(a))
+		{
+			req = req.header(hyper::header::AUTHORIZATION, value);
+		}
+
+		req
+	}
+
+	/// Sends a request, failing if the response headers aren't received within the read timeout
+	async fn request(
+		&self,
+		client: &Client<Connector>,
+		req: Request<Body>,
+	) -> Result<Response<Body>> {
+		match self.read_timeout {
+			Some(timeout) => tokio::time::timeout(timeout, client.request(req))
+				.await
+				.map_err(|_| Error::new(Kind::Timeout))?
+				.map_crate_err(),
+			None => client.request(req).await.map_crate_err(),
+		}
+	}
+
+	/// Sends a `GET` request to the given endpoint of the active host, see [`send_request_with`]
+	///
+	/// [`send_request_with`]: #method.send_request_with
+	async fn send_request(
+		&self,
+		client: &Client<Connector>,
+		endpoint: &str,
+	) -> Result<Response<Body>> {
+		self.send_request_with(client, endpoint, |req| {
+			req.body(Body::empty()).map_crate_err()
+		})
+		.await
+	}
+
+	/// Sends a `GET` request to a server log endpoint, see [`send_request_with`]. With the `gzip`
+	/// feature the body is requested gzip compressed, which the log entries usually shrink a lot
+	/// with
+	///
+	/// [`send_request_with`]: #method.send_request_with
+	async fn send_log_request(
+		&self,
+		client: &Client<Connector>,
+		endpoint: &str,
+	) -> Result<Response<Body>> {
+		self.send_request_with(client, endpoint, |req| {
+			#[cfg(feature = "gzip")]
+			let req = req.header(hyper::header::ACCEPT_ENCODING, "gzip");
+
+			req.body(Body::empty()).map_crate_err()
+		})
+		.await
+	}
+
+	/// Sends a request with the given method and JSON body to the given endpoint of the active
+	/// host, see [`send_request_with`]
+	///
+	/// [`send_request_with`]: #method.send_request_with
+	async fn send_json(
+		&self,
+		client: &Client<Connector>,
+		method: Method,
+		endpoint: &str,
+		body: Option<&JsonValue>,
+	) -> Result<Response<Body>> {
+		self.send_request_with(client, endpoint, |req| {
+			req.method(method.clone())
+				.header(hyper::header::CONTENT_TYPE, "application/json")
+				.body(body.map(|b| Body::from(b.to_string())).unwrap_or_default())
+				.map_crate_err()
+		})
+		.await
+	}
+
+	/// Sends a request to the given endpoint of the active host, retrying it on transient
+	/// failures following the [`RetryPolicy`] if any. On connection errors every failover host is
+	/// tried once before retrying. With [`TriggerAuthentication::jwt_login`] the JWT is obtained
+	/// before the first request and renewed once if the server responds `401 Unauthorized`. The
+	/// request is built again for each attempt from the authenticated [`HttpRequestBuilder`]
+	async fn send_request_with<F>(
+		&self,
+		client: &Client<Connector>,
+		endpoint: &str,
+		build: F,
+	) -> Result<Response<Body>>
+	where
+		F: Fn(HttpRequestBuilder) -> Result<Request<Body>>,
+	{
+		let mut attempt = 0;
+		let mut failovers = 0;
+		let mut renewed = false;
+		let mut rediscovered = false;
+
+		loop {
+			let uri = self.get_uri(endpoint)?;
+
+			let result = match self.login(client, false).await {
+				Ok(()) => {
+					let req = build(self.get_new_request(uri.clone()))?;
+
+					self.request(client, req).await
+				}
+				Err(e) if e.is_connection_error() => Err(e),
+				Err(e) => return Err(e),
+			};
+
+			if let Err(e) = &result {
+				if e.is_connection_error() && failovers + 1 < self.hosts.read().unwrap().len() {
+					let host = self.failover();
+
+					self.observer.report(
+						Severity::Warning,
+						DiagnosticCategory::Transport,
+						format!(
+							"request to {} failed ({}), failing over to {}",
+							uri, e, host
+						),
+					);
+
+					failovers += 1;
+					continue;
+				}
+			}
+
+			if let Ok(response) = &result {
+				if response.status() == StatusCode::UNAUTHORIZED && !renewed && self.renews_jwt() {
+					self.login(client, true).await?;
+					renewed = true;
+					continue;
+				}
+
+				if response.status() == StatusCode::SERVICE_UNAVAILABLE
+					&& self.leader_discovery
+					&& !rediscovered
+				{
+					rediscovered = true;
+
+					if self.discover_leader(client).await {
+						continue;
+					}
+				}
+			}
+
+			let transient = match &result {
+				Ok(response) => response.status().is_server_error(),
+				Err(_) => true,
+			};
+
+			match self.retry.as_ref().and_then(|r| r.retry_after(attempt)) {
+				Some(delay) if transient => {
+					let reason = match &result {
+						Ok(response) => response.status().to_string(),
+						Err(e) => e.to_string(),
+					};
+
+					self.observer.report(
+						Severity::Warning,
+						DiagnosticCategory::Transport,
+						format!(
+							"request to {} failed ({}), retrying in {:?}",
+							uri, reason, delay
+						),
+					);
+
+					tokio::time::sleep(delay).await;
+					attempt += 1;
+					failovers = 0;
+				}
+				_ => return result,
+			}
+		}
+	}
+
+	/// Initializes a [`Trigger`]. This method calls **`GET /_api/replication/logger-state`**
+	/// endpoint on the ArangoDB server to store the last log tick from ArangoDB Replication API on
+	/// the [`Trigger`] instance to then be used on the [`listen`] method. The [`ServerInfo`] is
+	/// fetched from **`GET /_api/version`** and kept, see [`server_info`]. With
+	/// [`LogApi::WalTail`], collection names are fetched from **`GET /_api/collection`** too, as
+	/// its log lines only reference collections by their globally unique id.
+	///
+	/// [`server_info`]: #method.server_info
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// returns: `Result<()>`, failing with a specific error if the replication logger is not
+	/// running on the server, the server doesn't support the replication API, like cluster
+	/// Coordinators, the server version doesn't support the [`LogApi`] in use, or the server log
+	/// no longer holds the tick of the [`ResumeToken`], see [`Error::is_tick_expired`]
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger.init().await.expect("Error initializing ArangoDB event trigger");
+	/// # }
+	/// ```
+	pub async fn init(&mut self) -> Result<()> {
+		let client = self.client()?;
+
+		if self.leader_discovery {
+			self.discover_leader(&client).await;
+		}
+
+		let data = self.get_logger_state(&client).await?;
+
+		self.server_id = data.server.map(|s| s.server_id);
+		self.events_per_tick = match data.state.last_log_tick.parse::<u64>() {
+			Ok(tick) if tick > 0 => data.state.total_events as f64 / tick as f64,
+			_ => 0.0,
+		};
+		self.catch_up = None;
+		self.server_info = self.fetch_server_info(&client).await?;
+		self.resolve_log_api(self.server_info.as_ref())?;
+		self.log.collections = self.fetch_collection_names(&client, self.log_api).await?;
+
+		self.load_configs(&client).await?;
+
+		if self.resume.is_none() {
+			if let Some(store) = &self.tick_store {
+				self.resume = store.load()?;
+			}
+		}
+
+		match self.resume.take() {
+			Some(token) => {
+				self.check_tick_range(&client, token.tick.as_str()).await?;
+
+				if self.barrier_ttl.is_some() && self.barrier_id.is_none() {
+					self.barrier_id = token.barrier_id.clone();
+				}
+
+				if token.server_id.is_some()
+					&& self.server_id.is_some()
+					&& token.server_id != self.server_id
+				{
+					return Err(Error::new(Kind::ArangoDB(ArangoDBError::ServerMismatch)));
+				}
+
+				self.log.restore(token.transactions)?;
+				self.last_log_tick = token.tick;
+			}
+			None => {
+				self.log.transactions.clear();
+				self.last_log_tick = data.state.last_log_tick;
+			}
+		}
+
+		self.caught_up = false;
+		self.seen_tick = 0;
+		self.last_scanned = 0;
+		self.probe_pending = self.startup_probe.is_some();
+
+		self.refresh_barrier(&client).await?;
+
+		Ok(())
+	}
+
+	/// Read-only health probe validating the [`Trigger`] configuration, meant for deployment
+	/// pre-flight checks. It connects and authenticates to the server, checks the replication
+	/// logger is running, that the server matches the resume token if any was given and still
+	/// holds its tick, that the server version supports the [`LogApi`] in use, and that the
+	/// replication log can be read. Neither the [`Trigger`] state nor the server state is changed
+	/// and no handler gets called, so it can be used before or instead of [`init`].
+	///
+	/// [`init`]: #method.init
+	///
+	/// returns: `Result<()>`, with the same errors [`init`] and [`listen`] would fail with
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// # async fn example() {
+	/// let trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger.preflight().await.expect("Invalid ArangoDB event trigger configuration");
+	/// # }
+	/// ```
+	pub async fn preflight(&self) -> Result<()> {
+		let client = self.client()?;
+		let data = self.get_logger_state(&client).await?;
+
+		let server_id = data.server.map(|s| s.server_id);
+
+		if let Some(token) = &self.resume {
+			if token.server_id.is_some() && server_id.is_some() && token.server_id != server_id {
+				return Err(Error::new(Kind::ArangoDB(ArangoDBError::ServerMismatch)));
+			}
+
+			self.check_tick_range(&client, token.tick.as_str()).await?;
+		}
+
+		let info = self.fetch_server_info(&client).await?;
+
+		let endpoint = format!(
+			"{}&chunkSize=1",
+			log_endpoint(
+				self.resolve_log_api(info.as_ref())?,
+				data.state.last_log_tick.as_str(),
+				0
+			)
+		);
+
+		let response = self.send_request(&client, endpoint.as_str()).await?;
+
+		match response.status() {
+			StatusCode::OK | StatusCode::NO_CONTENT => Ok(()),
+			_ => Err(response_error(response).await),
+		}
+	}
+
+	/// Replays a tick range of the replication log writing its [`Event`]s as NDJSON, one JSON
+	/// object per line with the event `tick`, `type` and `operation`, e.g. for audits or to
+	/// recover data. Events are normalized the same way they are dispatched: document operations
+	/// of a transaction come once it gets committed, and aborted transactions are left out. No
+	/// handler or callback gets called and the [`Trigger`] state is left untouched, so it doesn't
+	/// need to be initialized first. Operations of transactions started before `from` are not
+	/// exported.
+	///
+	/// # Arguments
+	///
+	/// * `from`: The exclusive lower bound tick
+	/// * `to`: The inclusive upper bound tick
+	/// * `out`: The writer the NDJSON lines are written to
+	///
+	/// returns: `Result<u64>`, the number of events written
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	/// use std::fs::File;
+	/// use std::io::BufWriter;
+	///
+	/// # async fn example() {
+	/// let trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// let out = BufWriter::new(File::create("events.ndjson").expect("Error creating file"));
+	///
+	/// let events = trigger
+	///     .export(1000, 2000, out)
+	///     .await
+	///     .expect("Error exporting the replication log");
+	///
+	/// println!("Exported {} events", events);
+	/// # }
+	/// ```
+	pub async fn export<W: std::io::Write>(&self, from: u64, to: u64, mut out: W) -> Result<u64> {
+		#[derive(serde::Serialize)]
+		struct ExportLine<'a> {
+			tick: u64,
+			#[serde(flatten)]
+			event: &'a Event,
+		}
+
+		let client = self.client()?;
+		let info = self.fetch_server_info(&client).await?;
+		let api = self.resolve_log_api(info.as_ref())?;
+		let mut log = LogState {
+			collections: self.fetch_collection_names(&client, api).await?,
+			..Default::default()
+		};
+		let mut batch = Vec::new();
+		let mut current_tick = from;
+		let mut last_scanned = 0;
+		let mut exported = 0;
+
+		while current_tick < to {
+			let endpoint = format!(
+				"{}&to={}",
+				log_endpoint(api, current_tick.to_string().as_str(), last_scanned),
+				to
+			);
+
+			let response = self.send_log_request(&client, endpoint.as_str()).await?;
+
+			if !matches!(response.status(), StatusCode::OK | StatusCode::NO_CONTENT) {
+				return Err(response_error(response).await);
+			}
+
+			check_from_present(response.headers(), current_tick.to_string().as_str())?;
+
+			let check_more = response
+				.headers()
+				.get(CHECK_MORE_HEADER)
+				.map(|v| v == "true")
+				.unwrap_or(false);
+
+			let last_included = match get_header(response.headers(), LAST_LOG_HEADER)? {
+				Some(v) => v.parse::<u64>().map_crate_err()?,
+				None => 0,
+			};
+
+			let scanned = match get_header(response.headers(), LAST_SCANNED_HEADER)? {
+				Some(v) => v.parse::<u64>().map_crate_err()?,
+				None => 0,
+			};
+
+			if last_included <= current_tick {
+				// wal/tail can include nothing while still scanning further
+				if check_more && scanned > last_scanned {
+					last_scanned = scanned;
+					continue;
+				}
+
+				break;
+			}
+
+			last_scanned = scanned;
+
+			let mut deserializer = Deserializer::new(response).with_timeout(self.read_timeout);
+
+			while let Some(line) = deserializer.read_line().await? {
+				let tick = get_line_tick(line.as_str())?
+					.parse::<u64>()
+					.map_crate_err()?;
+
+				if tick <= current_tick || tick > to {
+					continue;
+				}
+
+				process_line(&mut log, line, &mut batch, &self.observer)?;
+
+				for event in batch.drain(..) {
+					serde_json::to_writer(
+						&mut out,
+						&ExportLine {
+							tick,
+							event: &event,
+						},
+					)
+					.map_crate_err()?;
+					out.write_all(b"\n")?;
+
+					exported += 1;
+				}
+			}
+
+			current_tick = last_included;
+
+			if !check_more {
+				break;
+			}
+		}
+
+		out.flush()?;
+
+		Ok(exported)
+	}
+
+	/// Calls **`GET /_api/replication/logger-state`** endpoint on the ArangoDB server, failing if
+	/// the replication logger is not available
+	async fn get_logger_state(&self, client: &Client<Connector>) -> Result<LoggerStateData> {
+		let response = self
+			.send_request(client, "/_api/replication/logger-state")
+			.await?;
+
+		match response.status() {
+			StatusCode::OK => {
+				let bytes = hyper::body::to_bytes(response.into_body()).await?;
+				let data: LoggerStateData =
+					serde_json::from_slice(bytes.as_ref()).map_crate_err()?;
+
+				if !data.state.running {
+					return Err(Error::new(Kind::ArangoDB(
+						ArangoDBError::ReplicationUnavailable,
+					)));
+				}
+
+				Ok(data)
+			}
+			s @ StatusCode::NOT_IMPLEMENTED => Err(Error::new(Kind::ArangoDB(
+				ArangoDBError::ReplicationUnavailable,
+			))
+			.with(Error::from(s))),
+			_ => Err(response_error(response).await),
+		}
+	}
+
+	/// Compares the server last log tick with the [`Trigger`] one, following the
+	/// [`StartupProbe`]. Called on the first [`listen`] after [`init`]
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	async fn run_startup_probe(&mut self, client: &Client<Connector>) -> Result<()> {
+		self.probe_pending = false;
+
+		if let Some(probe) = self.startup_probe {
+			let data = self.get_logger_state(client).await?;
+
+			let advanced = data
+				.state
+				.last_log_tick
+				.parse::<u64>()
+				.map_crate_err()?
+				.saturating_sub(self.last_log_tick.parse().unwrap_or(0));
+
+			match probe {
+				StartupProbe::Warn { max_ticks } if advanced > max_ticks => self.observer.report(
+					Severity::Warning,
+					DiagnosticCategory::Checkpoint,
+					format!(
+						"server log advanced {} ticks between init() and the first listen()",
+						advanced
+					),
+				),
+				StartupProbe::Error { max_ticks } if advanced > max_ticks => {
+					return Err(Error::new(Kind::ArangoDB(ArangoDBError::StartupDrift)));
+				}
+				_ => {}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Listens to the ArangoDB Replication API calling to **`GET /_api/replication/logger-state`**
+	/// giving a query variable `from` the value of the last log tick stored on the [`Trigger`]
+	/// instance. This method doesn't keep listening to the server, but rather it returns whenever
+	/// the response of the HTTP request is already processed. To Keep this process running use this
+	/// method inside a loop and for multi-threading create a thread wrapping the loop, mind that
+	/// there shouldn't be any problems with [`HandlerContext`] data as they're [`std::sync::Arc`]
+	/// wrappers.
+	///
+	/// If the server reports the log entries following the stored tick were pruned, the call fails
+	/// with an error for which [`Error::is_tick_expired`] returns `true` rather than skipping
+	/// them, and keeps failing until the [`Trigger`] gets initialized again.
+	///
+	/// # Examples
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger.init().await.expect("Error initializing ArangoDB event trigger");
+	///
+	/// loop {
+	///     // Note that as this is a user-controlled loop, this whole listening process can be
+	///     // interrupted any time doing instead of a loop a while or any other systems
+	///     trigger.listen().await.unwrap();
+	/// }
+	/// # }
+	/// ```
+	pub async fn listen(&mut self) -> Result<()> {
+		let result = if self.database_missing {
+			self.recover_database().await
+		} else {
+			self.poll_log().await
+		};
+
+		let result = match result {
+			Err(e) if self.auto_reinit && e.is_database_not_found() => {
+				if !self.database_missing {
+					self.database_missing = true;
+
+					self.observer.report(
+						Severity::Warning,
+						DiagnosticCategory::Checkpoint,
+						format!(
+							"database {:?} not found, waiting for it to be recreated",
+							self.database
+						),
+					);
+				}
+
+				tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+				Ok(())
+			}
+			result => result,
+		};
+
+		if let Err(e) = &result {
+			self.observer
+				.report(Severity::Error, e.category(), e.to_string());
+		}
+
+		if !self.database_missing {
+			self.maintain_barrier().await;
+		}
+
+		self.check_silence();
+
+		result
+	}
+
+	/// Re-initializes the [`Trigger`] once its database exists again, notifying the collection
+	/// resets to the subscribed handlers, see [`with_auto_reinit`]
+	///
+	/// [`with_auto_reinit`]: #method.with_auto_reinit
+	async fn recover_database(&mut self) -> Result<()> {
+		self.init().await?;
+		self.log.transactions.clear();
+		self.database_missing = false;
+
+		for collection in self.subscriptions.collections() {
+			let reset = DocumentOperation::from_value(collection, serde_json::Value::Null);
+
+			let report = self
+				.subscriptions
+				.call(
+					HandlerEvent::CollectionReset,
+					&reset,
+					Some(reset.collection.as_str()),
+					self.dry_run.then_some(SkipReason::DryRun),
+					self.on_result.as_deref(),
+					None,
+				)
+				.await;
+
+			if let Some(callback) = &self.on_dispatch {
+				callback(&report);
+			}
+		}
+
+		if let Some(callback) = &self.on_database_reset {
+			callback();
+		}
+
+		Ok(())
+	}
+
+	/// Fetches and dispatches the next batch of the replication log, see [`listen`]
+	///
+	/// [`listen`]: #method.listen
+	async fn poll_log(&mut self) -> Result<()> {
+		if self.handle.is_paused() {
+			tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+			return Ok(());
+		}
+
+		let permit = match &self.scheduler {
+			Some(scheduler) => Some(scheduler.acquire().await),
+			None => None,
+		};
+
+		let started = Instant::now();
+		let current_tick = self.last_log_tick.clone();
+
+		let client = self.client()?;
+
+		if self.probe_pending {
+			self.run_startup_probe(&client).await?;
+		}
+
+		let mut endpoint = log_endpoint(self.log_api, current_tick.as_str(), self.last_scanned);
+
+		if self.max_body_size.is_some() {
+			endpoint.push_str(&format!("&chunkSize={}", self.chunk_size));
+		}
+
+		let response = self.send_log_request(&client, endpoint.as_str()).await?;
+		let response_time = started.elapsed();
+
+		if let Some(callback) = &self.on_response_headers {
+			callback(response.headers());
+		}
+
+		match response.status() {
+			StatusCode::OK | StatusCode::NO_CONTENT => {
+				check_from_present(response.headers(), current_tick.as_str())?;
+
+				let mut check_more = response
+					.headers()
+					.get(CHECK_MORE_HEADER)
+					.map(|v| v == "true")
+					.unwrap_or(false);
+
+				let server_tick = match get_header(response.headers(), LAST_TICK_HEADER)? {
+					Some(v) => Some(v.parse::<u64>().map_crate_err()?),
+					None => None,
+				};
+
+				if let Some(v) = get_header(response.headers(), LAST_SCANNED_HEADER)? {
+					self.last_scanned = v.parse::<u64>().map_crate_err()?;
+				}
+
+				let next_log_tick = if let Some(v) = response.headers().get(LAST_LOG_HEADER) {
+					let value = v.to_str().map_crate_err()?;
+
+					// wal/tail can include nothing while still scanning further, so the next call
+					// must go on right away from the last scanned tick
+					if value == "0" && check_more {
+						self.update_lag(server_tick, true);
+
+						return Ok(());
+					}
+
+					if value == "0" {
+						self.update_lag(None, false);
+						self.notify_caught_up();
+
+						if let Some(callback) = &self.on_event {
+							callback(&Event::Heartbeat);
+						}
+
+						self.record_poll(PollStats {
+							response_time,
+							duration: started.elapsed(),
+							body_size: 0,
+							events: 0,
+						});
+
+						// Don't keep the poll turn while idle
+						drop(permit);
+
+						tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+						return Ok(());
+					} else {
+						value
+					}
+				} else {
+					current_tick.as_str()
+				};
+
+				self.last_log_tick = next_log_tick.to_string();
+
+				let mut operations = 0;
+				let mut body_size = 0;
+
+				// If there's no change on tick value, call again process_log_tick
+				if !next_log_tick.eq(&current_tick) {
+					let mut deserializer =
+						Deserializer::new(response).with_timeout(self.read_timeout);
+					let mut batch = Vec::new();
+
+					let mut duplicates = 0;
+					let mut oversized = false;
+
+					while let Some(line) = deserializer.read_line().await? {
+						let tick = get_line_tick(line.as_str())?;
+						let tick_value = tick.parse::<u64>().map_crate_err()?;
+
+						// Servers can redeliver entries at batch boundaries, never process the
+						// same entry twice
+						if tick_value <= self.seen_tick {
+							duplicates += 1;
+							continue;
+						}
+
+						self.seen_tick = tick_value;
+
+						let pending = batch.len();
+						process_line(&mut self.log, line, &mut batch, &self.observer)?;
+						operations += batch.len() - pending;
+
+						if !self.buffers_batches() {
+							self.dispatch_batch(&mut batch).await;
+						}
+
+						if let Some(max) = self.max_batch_size {
+							// Defer the rest of the response to the next call, which will start
+							// right after the last processed line
+							if operations >= max {
+								self.last_log_tick = tick;
+								self.last_scanned = 0;
+								check_more = true;
+								break;
+							}
+						}
+
+						if let Some(max) = self.max_body_size {
+							// Abort the response and ask for smaller chunks from the last processed
+							// line on
+							if deserializer.bytes_read() > max {
+								self.last_log_tick = tick;
+								self.last_scanned = 0;
+								check_more = true;
+								oversized = true;
+								break;
+							}
+						}
+					}
+
+					if let Some(max) = self.max_body_size {
+						if oversized {
+							self.chunk_size = (self.chunk_size / 2).max(MIN_CHUNK_SIZE).min(max);
+
+							self.observer.report(
+								Severity::Warning,
+								DiagnosticCategory::Transport,
+								format!(
+									"response body exceeded {} bytes, reducing chunkSize to {}",
+									max, self.chunk_size
+								),
+							);
+						} else {
+							self.chunk_size = self.chunk_size.saturating_mul(2).min(max);
+						}
+					}
+
+					if duplicates > 0 {
+						self.observer.report(
+							Severity::Warning,
+							DiagnosticCategory::Checkpoint,
+							format!(
+								"suppressed {} log entries already processed on a previous call",
+								duplicates
+							),
+						);
+					}
+
+					self.dispatch_batch(&mut batch).await;
+					body_size = deserializer.bytes_read();
+				}
+
+				self.record_poll(PollStats {
+					response_time,
+					duration: started.elapsed(),
+					body_size,
+					events: operations,
+				});
+
+				self.update_lag(server_tick, check_more);
+
+				if !check_more {
+					self.notify_caught_up();
+				}
+
+				self.save_tick()
+			}
+			_ => Err(response_error(response).await),
+		}
+	}
+
+	/// Executes all the [`Event`]s of a batch, leaving it empty. If collection priorities are set
+	/// or stale operations are deprioritized the batch is sorted by them first, only reordering the
+	/// document operations between two other events so that none of them crosses a DDL operation.
+	/// Collection renames split the batch so that events are always dispatched against the right
+	/// subscriptions
+	async fn dispatch_batch(&mut self, batch: &mut Vec<Event>) {
+		let deprioritize_stale = matches!(self.deadline, Some((_, StalePolicy::Deprioritize)));
+
+		if !self.priorities.is_empty() || deprioritize_stale {
+			let documents = batch
+				.split_mut(|event| !matches!(event, Event::InsertOrReplace(_) | Event::Remove(_)));
+
+			for run in documents {
+				run.sort_by_cached_key(|event| {
+					(
+						deprioritize_stale && self.is_stale(event),
+						Reverse(
+							event
+								.collection()
+								.and_then(|c| self.priorities.get(c))
+								.copied()
+								.unwrap_or(0),
+						),
+					)
+				});
+			}
+		}
+
+		for event in batch.iter() {
+			if let Event::InsertOrReplace(doc) | Event::Remove(doc) = event {
+				self.stats
+					.record(doc.collection.as_str(), doc.raw_data().len() as u64);
+			}
+
+			if self.on_user_event.is_some() {
+				self.track_user(event);
+			}
+
+			if !self.configs.is_empty() {
+				self.track_config(event);
+			}
+
+			if let Some(collection) = event.collection() {
+				for watchdog in &mut self.silence_watchdogs {
+					if watchdog.collection == collection {
+						watchdog.last_event = Instant::now();
+						watchdog.alerted = false;
+					}
+				}
+			}
+		}
+
+		let mut segment = Vec::new();
+		let events = batch.len();
+
+		for event in batch.drain(..) {
+			let renamed = match event {
+				Event::Collection(ref op) if self.track_renames => op
+					.collection
+					.as_deref()
+					.zip(op.new_name())
+					.map(|(from, to)| (from.to_string(), to.to_string())),
+				_ => None,
+			};
+
+			if let Some((from, to)) = renamed {
+				self.dispatch_segment(&segment).await;
+				segment.clear();
+
+				self.execute_event(&event).await;
+				self.subscriptions.rename_collection(&from, &to);
+
+				for watchdog in &mut self.silence_watchdogs {
+					if watchdog.collection == from {
+						watchdog.collection.clone_from(&to);
+					}
+				}
+			} else {
+				segment.push(event);
+			}
+		}
+
+		self.dispatch_segment(&segment).await;
+		self.track_progress(events);
+	}
+
+	/// Applies a change of a configuration collection to its [`ConfigStore`]s
+	fn track_config(&self, event: &Event) {
+		let collection = match event.collection() {
+			Some(collection) => collection,
+			None => return,
+		};
+
+		for (_, store) in self.configs.iter().filter(|(c, _)| c == collection) {
+			match event {
+				Event::InsertOrReplace(doc) => match doc.data() {
+					Ok(data) => self.save_config(collection, store.as_ref(), data),
+					Err(e) => self.observer.report(
+						Severity::Warning,
+						DiagnosticCategory::Parse,
+						format!("skipping undecodable {} document: {}", collection, e),
+					),
+				},
+				Event::Remove(doc) => {
+					if let Some(key) = doc.field("_key").and_then(|k| k.as_str()) {
+						store.remove(key);
+					}
+				}
+				Event::Collection(op) if matches!(op.kind, DdlKind::Drop | DdlKind::Truncate) => {
+					store.clear()
+				}
+				_ => {}
+			}
+		}
+	}
+
+	/// Stores a configuration document, skipping it with a warning if it can't be decoded
+	fn save_config(&self, collection: &str, store: &dyn ConfigWatch, data: &JsonValue) {
+		if let Err(e) = store.save(data) {
+			self.observer.report(
+				Severity::Warning,
+				DiagnosticCategory::Parse,
+				format!("skipping undecodable {} document: {}", collection, e),
+			);
+		}
+	}
+
+	/// Loads the documents of every configuration collection into their [`ConfigStore`]s calling
+	/// **`POST /_api/cursor`**
+	async fn load_configs(&self, client: &Client<Connector>) -> Result<()> {
+		for (collection, store) in &self.configs {
+			let mut batch = Some(self.open_cursor(client, collection).await?);
+
+			store.clear();
+
+			while let Some(data) = batch.take() {
+				for doc in &data.result {
+					self.save_config(collection, store.as_ref(), doc);
+				}
+
+				batch = self.next_batch(client, &data).await?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Runs an AQL query returning all the documents of a collection calling
+	/// **`POST /_api/cursor`**, returning the first batch of results. Later batches are read with
+	/// [`next_batch`]
+	///
+	/// [`next_batch`]: #method.next_batch
+	async fn open_cursor(
+		&self,
+		client: &Client<Connector>,
+		collection: &str,
+	) -> Result<CursorResponse> {
+		let body = serde_json::json!({
+			"query": "FOR d IN @@collection RETURN d",
+			"bindVars": { "@collection": collection },
+			"batchSize": 1000,
+			"options": { "allowRetry": true },
+		});
+
+		let response = self
+			.send_json(client, Method::POST, "/_api/cursor", Some(&body))
+			.await?;
+
+		read_cursor(response).await
+	}
+
+	/// Reads the batch following the given one, or returns [`None`] if it was the last one.
+	/// Servers sending batch ids are called **`POST /_api/cursor/{id}/{batch}`**, which can be
+	/// safely retried, and **`PUT /_api/cursor/{id}`** otherwise
+	async fn next_batch(
+		&self,
+		client: &Client<Connector>,
+		data: &CursorResponse,
+	) -> Result<Option<CursorResponse>> {
+		let id = match &data.id {
+			Some(id) if data.has_more => id,
+			_ => return Ok(None),
+		};
+
+		let (method, endpoint) = match data.next_batch() {
+			Some(batch) => (Method::POST, format!("/_api/cursor/{}/{}", id, batch)),
+			None => (Method::PUT, format!("/_api/cursor/{}", id)),
+		};
+
+		let response = self
+			.send_json(client, method, endpoint.as_str(), None)
+			.await?;
+
+		read_cursor(response).await.map(Some)
+	}
+
+	/// Deletes the server cursor of the given batch calling **`DELETE /_api/cursor/{id}`**, so it
+	/// doesn't hold resources until its `ttl` expires. Failures are reported as warnings
+	async fn close_cursor(&self, client: &Client<Connector>, data: &CursorResponse) {
+		let id = match &data.id {
+			Some(id) if data.has_more => id,
+			_ => return,
+		};
+
+		let endpoint = format!("/_api/cursor/{}", id);
+
+		let result = match self
+			.send_json(client, Method::DELETE, endpoint.as_str(), None)
+			.await
+		{
+			Ok(response) if response.status().is_success() => return,
+			Ok(response) => response.status().to_string(),
+			Err(e) => e.to_string(),
+		};
+
+		self.observer.report(
+			Severity::Warning,
+			DiagnosticCategory::Transport,
+			format!("unable to delete cursor {}: {}", id, result),
+		);
+	}
+
+	/// Decodes a document operation on the `_users` system collection into a [`UserEvent`] and
+	/// calls the user callback with it, keeping the last version of each user
+	fn track_user(&mut self, event: &Event) {
+		let user_event = match event {
+			Event::InsertOrReplace(doc) if doc.collection == USERS_COLLECTION => {
+				match doc.deserialize_data::<UserDocument>() {
+					Ok(user) => UserEvent::Saved {
+						previous: self.users.insert(user.key.clone(), user.clone()),
+						user,
+					},
+					Err(e) => {
+						self.observer.report(
+							Severity::Warning,
+							DiagnosticCategory::Parse,
+							format!("skipping undecodable {} document: {}", USERS_COLLECTION, e),
+						);
+
+						return;
+					}
+				}
+			}
+			Event::Remove(doc) if doc.collection == USERS_COLLECTION => {
+				let key = match doc.field("_key").and_then(|k| k.as_str()) {
+					Some(key) => key.to_string(),
+					None => return,
+				};
+
+				UserEvent::Removed {
+					previous: self.users.remove(&key),
+					key,
+				}
+			}
+			_ => return,
+		};
+
+		if let Some(callback) = &self.on_user_event {
+			callback(&user_event);
+		}
+	}
+
+	/// Executes a list of [`Event`]s following the [`DispatchMode`]
+	async fn dispatch_segment(&self, events: &[Event]) {
+		match self.dispatch_mode {
+			DispatchMode::Serial => {
+				for event in events {
+					self.execute_event(event).await
+				}
+			}
+			DispatchMode::PerCollection => {
+				let mut collections: Vec<(Option<&str>, Vec<&Event>)> = Vec::new();
+
+				for event in events {
+					let collection = event.collection();
+
+					if let Some((_, evs)) = collections.iter_mut().find(|(c, _)| *c == collection) {
+						evs.push(event);
+					} else {
+						collections.push((collection, vec![event]));
+					}
+				}
+
+				join_all(collections.into_iter().map(|(_, evs)| async move {
+					for event in evs {
+						self.execute_event(event).await
+					}
+				}))
+				.await;
+			}
+		}
+	}
+
+	/// Executes an [`Event`], dispatching it to the subscribed handlers if it's a document
+	/// operation or a collection reset (drop or truncate)
+	async fn execute_event(&self, event: &Event) {
+		if let Some(callback) = &self.on_event {
+			callback(event);
+		}
+
+		let reset;
+
+		let (handler_event, doc) = match event {
+			Event::InsertOrReplace(doc) => (HandlerEvent::InsertOrReplace, doc),
+			Event::Remove(doc) => (HandlerEvent::Remove, doc),
+			Event::Collection(op) if matches!(op.kind, DdlKind::Drop | DdlKind::Truncate) => {
+				match &op.collection {
+					Some(collection) => {
+						reset = DocumentOperation::from_value(collection.clone(), op.data.clone());
+
+						(HandlerEvent::CollectionReset, &reset)
+					}
+					None => return,
+				}
+			}
+			_ => return,
+		};
+
+		let skip = match self.deadline {
+			Some((_, StalePolicy::Skip)) if self.is_stale(event) => Some(SkipReason::Expired),
+			_ if self.dry_run => Some(SkipReason::DryRun),
+			_ => None,
+		};
+
+		let mut report = self
+			.subscriptions
+			.call(
+				handler_event,
+				doc,
+				Some(doc.collection.as_str()),
+				skip,
+				self.on_result.as_deref(),
+				None,
+			)
+			.await;
+
+		for dispatch in &report.handlers {
+			if dispatch.outcome == DispatchOutcome::Called {
+				self.stats.record_handler(
+					dispatch.handler,
+					doc.collection.as_str(),
+					dispatch.duration,
+					|| self.document_key(doc),
+				);
+			}
+		}
+
+		if let Some(callback) = &self.on_dispatch {
+			report.key = self.document_key(doc);
+
+			callback(&report);
+		}
+	}
+
+	/// Runs a [`Trigger`] on a dedicated OS thread with its own single-threaded runtime, calling
+	/// [`init`] and then [`listen`] in a loop until it's stopped through the returned
+	/// [`TriggerThread`] or fails. This makes it possible to embed the Trigger into synchronous
+	/// applications. As handler futures are not [`Send`], the [`Trigger`] keeps running on its own
+	/// thread, named `arangodb-events-trigger`, and it's built there by the given function.
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `build`: The function building the [`Trigger`] and its subscriptions
+	///
+	/// returns: [`TriggerThread`]
+	///
+	/// # Examples
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let thread = Trigger::run_on_dedicated_thread(|| {
+	///     Trigger::new(
+	///         "http://localhost:8529/",
+	///         "alchemy",
+	///     )
+	/// });
+	///
+	/// // ...
+	///
+	/// thread.stop().expect("ArangoDB Trigger failed");
+	/// ```
+	pub fn run_on_dedicated_thread<F>(build: F) -> TriggerThread
+	where
+		F: FnOnce() -> Trigger + Send + 'static,
+	{
+		let (sender, receiver) = std::sync::mpsc::channel();
+
+		let thread = std::thread::Builder::new()
+			.name(String::from("arangodb-events-trigger"))
+			.spawn(move || {
+				let mut trigger = build();
+				let handle = trigger.handle();
+				let _ = sender.send(handle.clone());
+
+				let runtime = tokio::runtime::Builder::new_current_thread()
+					.enable_all()
+					.build()?;
+
+				runtime.block_on(async {
+					trigger.init().await?;
+
+					while !handle.is_stopped() {
+						trigger.listen().await?;
+					}
+
+					Ok(())
+				})
+			})
+			.expect("failed to spawn the Trigger thread");
+
+		match receiver.recv() {
+			Ok(handle) => TriggerThread::new(handle, thread),
+			// The build function panicked before the handle was sent
+			Err(_) => match thread.join() {
+				Err(panic) => std::panic::resume_unwind(panic),
+				Ok(_) => unreachable!("Trigger thread ended without sending its handle"),
+			},
+		}
+	}
+
+	/// Subscribes [`Handler`] to a [`HandlerEvent`] with a given [`HandlerContext`]
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
+	/// * `ctx`: The [`Handler::Context`] wrapped on a [`HandlerContext`]. As [`HandlerContext`]s are
+	///   type-erased, its type is checked against the [`Handler::Context`] one
+	///
+	/// returns: `Result<()>`, failing if the context type is not the [`Handler::Context`] one or if
+	/// the [`Handler`] is already subscribed to the [`HandlerEvent`] and the [`DuplicatePolicy`] is
+	/// [`DuplicatePolicy::Error`]
+	///
+	/// # Examples
+	/// ```no_run
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	///
+	/// pub struct ExampleHandler;
+	///
+	/// pub struct MyContext {
+	///     pub data: u8,
+	/// }
+	///
+	/// impl Handler for ExampleHandler {
+	///     type Context = MyContext;
+	///
+	///     fn call(ctx: &MyContext, doc: &DocumentOperation) {
+	///         println!("{}", ctx.data); // 10
+	///     }
+	/// }
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger.subscribe::<ExampleHandler>(
+	///     HandlerEvent::InsertOrReplace,
+	///     HandlerContextFactory::from(MyContext {
+	///         data: 10,
+	///     })
+	/// ).unwrap();
+	///
+	/// trigger
+	///     .init()
+	///     .await
+	///     .expect("Error initializing ArangoDB Trigger");
+	///
+	/// loop {
+	///     trigger.listen().await.unwrap();
+	/// }
+	/// # }
+	/// ```
+	pub fn subscribe<H: Handler>(
+		&mut self,
+		event: HandlerEvent,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.subscriptions.subscribe::<H>(event, ctx)
+	}
+
+	/// Subscribes [`Handler`] to a [`HandlerEvent`] with a given [`HandlerContext`] for all
+	/// document operations that affects given collection name
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
+	/// * `collection`: The ArangoDB collection name
+	/// * `ctx`: The [`Handler::Context`] wrapped on a [`HandlerContext`]. As [`HandlerContext`]s are
+	///   type-erased, its type is checked against the [`Handler::Context`] one
+	///
+	/// returns: `Result<()>`, failing if the context type is not the [`Handler::Context`] one or if
+	/// the [`Handler`] is already subscribed to the [`HandlerEvent`] for the same collection and
+	/// the [`DuplicatePolicy`] is [`DuplicatePolicy::Error`]
+	///
+	/// # Examples
+	/// ```no_run
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	///
+	/// pub struct AccountHandler;
+	///
+	/// pub struct AccountContext {
+	///     pub data: u8,
+	/// }
+	///
+	/// impl Handler for AccountHandler {
+	///     type Context = AccountContext;
+	///
+	///     fn call(ctx: &AccountContext, doc: &DocumentOperation) {
+	///         println!("{}", ctx.data); // 10
+	///     }
+	/// }
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger.subscribe_to::<AccountHandler>(
+	///     HandlerEvent::InsertOrReplace,
+	///     "accounts",
+	///     HandlerContextFactory::from(AccountContext {
+	///         data: 10,
+	///     })
+	/// ).unwrap();
+	///
+	/// trigger
+	///     .init()
+	///     .await
+	///     .expect("Error initializing ArangoDB Trigger");
+	///
+	/// loop {
+	///     trigger.listen().await.unwrap();
+	/// }
+	/// # }
+	/// ```
+	pub fn subscribe_to<H: Handler>(
+		&mut self,
+		event: HandlerEvent,
+		collection: &str,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.subscriptions.subscribe_to::<H>(event, collection, ctx)
+	}
+
+	/// Subscribes [`Handler`] to a [`HandlerEvent`] for all document operations that affects any
+	/// of the given collection names, sharing the same [`HandlerContext`] across all of them
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
+	/// * `collections`: The ArangoDB collection names
+	/// * `ctx`: The [`Handler::Context`] wrapped on a [`HandlerContext`], shared by all the
+	///   subscriptions
+	///
+	/// returns: `Result<()>`, failing like [`subscribe_to`] on the first collection that can't be
+	/// subscribed to, keeping the subscriptions to the previous ones
+	///
+	/// [`subscribe_to`]: #method.subscribe_to
+	///
+	/// # Examples
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	///
+	/// pub struct AuditHandler;
+	///
+	/// impl Handler for AuditHandler {
+	///     type Context = ();
+	///
+	///     fn call(_: &(), doc: &DocumentOperation) {
+	///         println!("audit {}", doc.collection);
+	///     }
+	/// }
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// trigger
+	///     .subscribe_to_many::<AuditHandler>(
+	///         HandlerEvent::InsertOrReplace,
+	///         &["accounts", "orders", "invoices"],
+	///         HandlerContextFactory::from(()),
+	///     )
+	///     .unwrap();
+	/// ```
+	pub fn subscribe_to_many<H: Handler>(
+		&mut self,
+		event: HandlerEvent,
+		collections: &[&str],
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		for collection in collections {
+			self.subscriptions
+				.subscribe_to::<H>(event, collection, ctx.clone())?;
+		}
+
+		Ok(())
+	}
+
+	/// Subscribes [`OutputHandler`] to a [`HandlerEvent`] with a given [`HandlerContext`]. The
+	/// values it returns are forwarded to the [`on_result`] callback
+	///
+	/// [`on_result`]: #method.on_result
+	///
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`OutputHandler`] is gonna listen to
+	/// * `ctx`: The [`OutputHandler::Context`] wrapped on a [`HandlerContext`]. As
+	///   [`HandlerContext`]s are type-erased, its type is checked against the
+	///   [`OutputHandler::Context`] one
+	///
+	/// returns: `Result<()>`, failing like [`subscribe`]
+	///
+	/// [`subscribe`]: #method.subscribe
+	///
+	/// # Examples
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{HandlerContextFactory, HandlerEvent, OutputHandler, Trigger};
+	///
+	/// pub struct KeyHandler;
+	///
+	/// impl OutputHandler for KeyHandler {
+	///     type Context = ();
+	///     type Output = Option<String>;
+	///
+	///     fn call(_: &(), doc: &DocumentOperation) -> Option<String> {
+	///         doc.field("_key").and_then(|v| v.as_str()).map(String::from)
+	///     }
+	/// }
+	///
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// )
+	/// .on_result(|result| println!("{:?}", result.downcast_ref::<Option<String>>()));
+	///
+	/// trigger
+	///     .subscribe_output::<KeyHandler>(HandlerEvent::Remove, HandlerContextFactory::from(()))
+	///     .unwrap();
+	/// ```
+	pub fn subscribe_output<H: OutputHandler>(
+		&mut self,
+		event: HandlerEvent,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.subscriptions.subscribe_output::<H>(event, ctx)
+	}
+
+	/// Subscribes [`OutputHandler`] to a [`HandlerEvent`] with a given [`HandlerContext`] for all
+	/// document operations that affects given collection name. The values it returns are
+	/// forwarded to the [`on_result`] callback
 	///
-	/// impl Handler for GlobalHandler {
-	///     type Context = GlobalHandlerContext;
+	/// [`on_result`]: #method.on_result
 	///
-	///     fn call(ctx: &GlobalHandlerContext, doc: &DocumentOperation) {
-	///         println!("{}", ctx.data); // 10
+	/// # Arguments
+	///
+	/// * `ev`: The [`HandlerEvent`] the [`OutputHandler`] is gonna listen to
+	/// * `collection`: The ArangoDB collection name
+	/// * `ctx`: The [`OutputHandler::Context`] wrapped on a [`HandlerContext`]
+	///
+	/// returns: `Result<()>`, failing like [`subscribe_to`]
+	///
+	/// [`subscribe_to`]: #method.subscribe_to
+	pub fn subscribe_output_to<H: OutputHandler>(
+		&mut self,
+		event: HandlerEvent,
+		collection: &str,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		self.subscriptions
+			.subscribe_output_to::<H>(event, collection, ctx)
+	}
+
+	/// Watches a configuration collection, returning a [`ConfigStore`] holding its documents
+	/// decoded into `T`. The documents are loaded on [`init`] and kept up to date from then on
+	/// with the changes received from the replication log, which are applied before the events
+	/// of the same [`listen`] batch get dispatched. Documents that can't be decoded are skipped
+	/// with a warning. Place the returned store on the contexts of the handlers that need it
+	///
+	/// [`init`]: #method.init
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The ArangoDB collection name
+	///
+	/// returns: [`ConfigStore<T>`]
+	///
+	/// # Examples
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{ConfigStore, Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	/// use serde::Deserialize;
+	///
+	/// #[derive(Deserialize)]
+	/// pub struct FeatureFlag {
+	///     pub enabled: bool,
+	/// }
+	///
+	/// pub struct NotifyHandler;
+	///
+	/// impl Handler for NotifyHandler {
+	///     type Context = ConfigStore<FeatureFlag>;
+	///
+	///     fn call(flags: &ConfigStore<FeatureFlag>, doc: &DocumentOperation) {
+	///         if flags.get("notifications").map(|f| f.enabled).unwrap_or(false) {
+	///             println!("notify {}", doc.collection);
+	///         }
 	///     }
 	/// }
 	///
 	/// let mut trigger = Trigger::new(
-	/// 	"http://localhost:8529/",
-	/// 	"alchemy",
-	/// );
-	///
-	///	trigger.subscribe::<Example>(
-	/// 	HandlerEvent::InsertOrReplace,
-	/// 	HandlerContextFactory::from(MyContext {
-	///         data: 10,
-	///  	})
+	///     "http://localhost:8529/",
+	///     "alchemy",
 	/// );
 	///
-	///	trigger
-	///		.init()
-	///		.await
-	///		.expect("Error initializing ArangoDB Trigger");
+	/// let flags = trigger.watch_config::<FeatureFlag>("feature_flags");
 	///
-	///  loop {
-	///		trigger.listen().await.unwrap();
-	///  }
+	/// trigger
+	///     .subscribe_to::<NotifyHandler>(
+	///         HandlerEvent::InsertOrReplace,
+	///         "orders",
+	///         HandlerContextFactory::from(flags),
+	///     )
+	///     .unwrap();
 	/// ```
-	pub fn subscribe<H: Handler>(&mut self, event: HandlerEvent, ctx: HandlerContext<dyn Any>) {
-		self.subscriptions.insert::<H>(event, ctx)
+	pub fn watch_config<T: DeserializeOwned + Send + Sync + 'static>(
+		&mut self,
+		collection: &str,
+	) -> ConfigStore<T> {
+		let store = ConfigStore::new();
+
+		self.configs
+			.push((collection.to_string(), Box::new(store.clone())));
+
+		store
 	}
 
-	/// Subscribes [`Handler`] to a [`HandlerEvent`] with a given [`HandlerContext`] for all
-	/// document operations that affects given collection name
+	/// Subscribes [`Handler`] to all the [`HandlerEvent`]s listed on its [`Handler::EVENTS`]
+	/// constant with a given [`HandlerContext`], so the events a handler deals with are declared
+	/// next to its logic
 	///
 	/// # Arguments
 	///
-	/// * `ev`: The [`HandlerEvent`] the [`Handler`] is gonna listen to
-	/// * `collection`: The ArangoDB collection name
-	/// * `ctx`: The [`Handler::Context`]. Note that you could pass here any [`HandlerContext`]
-	/// with any type, but note that if its type it's not the same as the [`Handler::Context`] one
-	/// the [`Handler::call`] method is never gonna be called as downcasting will fail
+	/// * `ctx`: The [`Handler::Context`], shared by all the subscriptions
+	///
+	/// returns: `Result<()>`, failing if the context type is not the [`Handler::Context`] one or if
+	/// the [`Handler`] is already subscribed to any of the [`HandlerEvent`]s and the
+	/// [`DuplicatePolicy`] is [`DuplicatePolicy::Error`]
 	///
 	/// # Examples
 	/// ```
 	/// use arangodb_events_rs::api::DocumentOperation;
 	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
 	///
-	/// pub struct AccountHandler;
+	/// pub struct SearchIndexHandler;
 	///
-	/// pub struct AccountContext {
-	///     pub data: u8,
-	/// }
+	/// impl Handler for SearchIndexHandler {
+	///     type Context = ();
 	///
-	/// impl Handler for AccountHandler {
-	///     type Context = AccountContext;
+	///     const EVENTS: &'static [HandlerEvent] = &[
+	///         HandlerEvent::InsertOrReplace,
+	///         HandlerEvent::Remove,
+	///     ];
 	///
-	///     fn call(ctx: &AccountContext, doc: &DocumentOperation) {
-	///         println!("{}", ctx.data); // 10
+	///     fn call(_: &(), doc: &DocumentOperation) {
+	///         println!("reindexing {}", doc.collection);
 	///     }
 	/// }
 	///
 	/// let mut trigger = Trigger::new(
-	/// 	"http://localhost:8529/",
-	/// 	"alchemy",
+	///     "http://localhost:8529/",
+	///     "alchemy",
 	/// );
 	///
-	///	trigger.subscribe_to::<AccountHandler>(
-	/// 	HandlerEvent::InsertOrReplace,
-	/// 	"accounts",
-	/// 	HandlerContextFactory::from(AccountContext {
-	///         data: 10,
-	///  	})
+	/// trigger
+	///     .subscribe_auto::<SearchIndexHandler>(HandlerContextFactory::from(()))
+	///     .unwrap();
+	/// ```
+	pub fn subscribe_auto<H: Handler>(
+		&mut self,
+		ctx: HandlerContext<dyn Any + Send + Sync>,
+	) -> Result<()> {
+		for event in H::EVENTS {
+			self.subscriptions.subscribe::<H>(*event, ctx.clone())?;
+		}
+
+		Ok(())
+	}
+
+	/// Rebuilds the state a [`Handler`] derives from a collection, e.g. to reindex a single
+	/// consumer. The [`Handler`] subscriptions for the collection, global or attached to it, get
+	/// a [`HandlerEvent::CollectionReset`] to drop their state, followed by a
+	/// [`HandlerEvent::InsertOrReplace`] per document of a snapshot of the collection read with
+	/// **`POST /_api/cursor`**, if they are subscribed to those events. Other handlers are not
+	/// called.
+	///
+	/// As it borrows the [`Trigger`] mutably, live events are paused for every handler while
+	/// rebuilding, not only for the rebuilt one. They resume from the current [`Trigger`] tick
+	/// with the next [`listen`] call, so no change is missed. Changes made between that tick and
+	/// the snapshot are delivered to the [`Handler`] again, so its operations must be idempotent.
+	/// Snapshot requests are retried like any other request, and the server cursor is deleted if
+	/// reading it fails.
+	///
+	/// [`listen`]: #method.listen
+	///
+	/// # Arguments
+	///
+	/// * `collection`: The collection name
+	///
+	/// returns: `Result<u64>`, the number of documents replayed
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	///
+	/// pub struct SearchIndexer;
+	///
+	/// impl Handler for SearchIndexer {
+	///     type Context = ();
+	///
+	///     const EVENTS: &'static [HandlerEvent] = &[
+	///         HandlerEvent::InsertOrReplace,
+	///         HandlerEvent::Remove,
+	///         HandlerEvent::CollectionReset,
+	///     ];
+	///
+	///     fn call(_: &(), doc: &DocumentOperation) {
+	///         println!("index {}", doc.collection);
+	///     }
+	/// }
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
 	/// );
 	///
-	///	trigger
-	///		.init()
-	///		.await
-	///		.expect("Error initializing ArangoDB Trigger");
+	/// trigger.subscribe_auto::<SearchIndexer>(HandlerContextFactory::from(())).unwrap();
+	/// trigger.init().await.unwrap();
+	///
+	/// trigger.rebuild::<SearchIndexer>("products").await.unwrap();
 	///
-	///  loop {
-	///		trigger.listen().await.unwrap();
-	///  }
+	/// loop {
+	///     trigger.listen().await.unwrap();
+	/// }
+	/// # }
 	/// ```
-	pub fn subscribe_to<H: Handler>(
-		&mut self,
+	pub async fn rebuild<H: Handler>(&mut self, collection: &str) -> Result<u64> {
+		let handler = TypeId::of::<H>();
+		let events = self.subscriptions.handler_events(handler, collection);
+
+		let skip = self.dry_run.then_some(SkipReason::DryRun);
+		let mut replayed = 0;
+
+		if events.contains(&HandlerEvent::CollectionReset) {
+			let reset = DocumentOperation::from_value(collection.to_string(), JsonValue::Null);
+
+			self.replay(handler, HandlerEvent::CollectionReset, &reset, skip)
+				.await;
+		}
+
+		if !events.contains(&HandlerEvent::InsertOrReplace) {
+			return Ok(replayed);
+		}
+
+		let client = self.client()?;
+
+		let mut batch = Some(self.open_cursor(&client, collection).await?);
+
+		while let Some(mut data) = batch.take() {
+			for value in std::mem::take(&mut data.result) {
+				let doc = DocumentOperation::from_value(collection.to_string(), value);
+
+				self.replay(handler, HandlerEvent::InsertOrReplace, &doc, skip)
+					.await;
+
+				replayed += 1;
+			}
+
+			batch = match self.next_batch(&client, &data).await {
+				Ok(batch) => batch,
+				Err(e) => {
+					self.close_cursor(&client, &data).await;
+
+					return Err(e);
+				}
+			};
+		}
+
+		Ok(replayed)
+	}
+
+	/// Dispatches a document operation to the subscriptions of a single handler only, see
+	/// [`rebuild`]
+	///
+	/// [`rebuild`]: #method.rebuild
+	async fn replay(
+		&self,
+		handler: TypeId,
 		event: HandlerEvent,
-		collection: &str,
-		ctx: HandlerContext<dyn Any>,
+		doc: &DocumentOperation,
+		skip: Option<SkipReason>,
 	) {
-		self.subscriptions.insert_to::<H>(event, collection, ctx)
+		let report = self
+			.subscriptions
+			.call(
+				event,
+				doc,
+				Some(doc.collection.as_str()),
+				skip,
+				self.on_result.as_deref(),
+				Some(handler),
+			)
+			.await;
+
+		if let Some(callback) = &self.on_dispatch {
+			callback(&report);
+		}
+	}
+
+	/// Returns the [`SubscriptionManager`] of the [`Trigger`]. As it's shared, handlers can be
+	/// subscribed through it from any thread or task, even while the [`Trigger`] is listening
+	///
+	/// returns: `Arc<SubscriptionManager>`
+	///
+	/// # Examples
+	/// ```
+	/// use arangodb_events_rs::api::DocumentOperation;
+	/// use arangodb_events_rs::{Handler, HandlerContextFactory, HandlerEvent, Trigger};
+	///
+	/// pub struct LateHandler;
+	///
+	/// impl Handler for LateHandler {
+	///     type Context = ();
+	///
+	///     fn call(_: &(), doc: &DocumentOperation) {
+	///         println!("{}", doc.collection);
+	///     }
+	/// }
+	///
+	/// let trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// let subscriptions = trigger.subscriptions();
+	///
+	/// std::thread::spawn(move || {
+	///     subscriptions
+	///         .subscribe::<LateHandler>(HandlerEvent::Remove, HandlerContextFactory::from(()))
+	///         .unwrap();
+	/// })
+	/// .join()
+	/// .unwrap();
+	/// ```
+	pub fn subscriptions(&self) -> Arc<SubscriptionManager> {
+		self.subscriptions.clone()
+	}
+
+	/// Returns a snapshot of the per-handler invocation metrics, labelled by handler type name,
+	/// collection and [`HandlerEvent`]
+	///
+	/// returns: `HashMap<HandlerMetricLabels, HandlerMetrics>`
+	///
+	/// # Examples
+	/// ```
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// let trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// for (labels, metrics) in trigger.handler_metrics() {
+	///     println!(
+	///         "{} {} {:?}: {} calls, {:?} spent",
+	///         labels.handler, labels.collection, labels.event, metrics.count, metrics.duration
+	///     );
+	/// }
+	/// ```
+	#[cfg(feature = "metrics")]
+	pub fn handler_metrics(&self) -> HashMap<HandlerMetricLabels, HandlerMetrics> {
+		self.subscriptions.metrics.snapshot()
+	}
+
+	/// Returns a future running a tiny HTTP server on the given address that exposes the
+	/// [`Trigger`] metrics on **`GET /metrics`** in the Prometheus text format and its
+	/// [`TriggerHealth`] on **`GET /health`**, responding `503 Service Unavailable` while
+	/// degraded. The future doesn't borrow the [`Trigger`], so it can be spawned next to the
+	/// listening loop.
+	///
+	/// # Arguments
+	///
+	/// * `addr`: The address the HTTP server listens on
+	///
+	/// returns: `impl Future<Output = Result<()>>`, failing if the address can't be bound or the
+	/// server stops with an error
+	///
+	/// # Examples
+	/// ```no_run
+	/// use arangodb_events_rs::Trigger;
+	///
+	/// # async fn example() {
+	/// let mut trigger = Trigger::new(
+	///     "http://localhost:8529/",
+	///     "alchemy",
+	/// );
+	///
+	/// tokio::spawn(trigger.metrics_server(([0, 0, 0, 0], 9000).into()));
+	///
+	/// trigger.init().await.expect("Error initializing ArangoDB event trigger");
+	///
+	/// loop {
+	///     trigger.listen().await.unwrap();
+	/// }
+	/// # }
+	/// ```
+	#[cfg(feature = "metrics-server")]
+	pub fn metrics_server(
+		&self,
+		addr: std::net::SocketAddr,
+	) -> impl std::future::Future<Output = Result<()>> + Send + 'static {
+		crate::metrics::serve(self.subscriptions.metrics.clone(), addr)
+	}
+}
+
+/// Gets the string value of an HTTP Header if present
+fn get_header<'a>(headers: &'a HeaderMap, name: &str) -> Result<Option<&'a str>> {
+	headers
+		.get(name)
+		.map(|v| v.to_str().map_crate_err())
+		.transpose()
+}
+
+/// Fails with a `TickExpired` [`Error`] if the server log response tells the entries following
+/// the `from` tick are no longer present
+fn check_from_present(headers: &HeaderMap, from: &str) -> Result<()> {
+	match get_header(headers, FROM_PRESENT_HEADER)? {
+		Some("false") => Err(Error::new(Kind::ArangoDB(ArangoDBError::TickExpired))
+			.with(format!("log entries following tick {} were pruned", from))),
+		_ => Ok(()),
+	}
+}
+
+/// Builds the [`Error`] of a non-successful response, reading its body to tell apart a missing
+/// database from any other HTTP error
+async fn response_error(response: Response<Body>) -> Error {
+	let status = response.status();
+
+	if status == StatusCode::NOT_FOUND {
+		if let Ok(bytes) = hyper::body::to_bytes(response.into_body()).await {
+			if let Ok(body) = serde_json::from_slice::<ErrorResponse>(bytes.as_ref()) {
+				if body.error_num == DATABASE_NOT_FOUND_ERROR {
+					return Error::new(Kind::ArangoDB(ArangoDBError::DatabaseNotFound))
+						.with(Error::from(status));
+				}
+			}
+		}
+	}
+
+	status.into()
+}
+
+/// Reads the batch of results of a cursor response
+async fn read_cursor(response: Response<Body>) -> Result<CursorResponse> {
+	if response.status() != StatusCode::OK && response.status() != StatusCode::CREATED {
+		return Err(response_error(response).await);
+	}
+
+	let bytes = hyper::body::to_bytes(response.into_body()).await?;
+
+	serde_json::from_slice(bytes.as_ref()).map_crate_err()
+}
+
+/// Gets the tick of a logger line
+fn get_line_tick(line: &str) -> Result<String> {
+	let search = "\"tick\":\"";
+
+	let tick_idx = line
+		.find(search)
+		.ok_or(Error::new(Kind::Io(Io::Serialize)))?
+		+ search.len();
+
+	Ok(utils::get_string_until(line, tick_idx, '"'))
+}
+
+/// Returns the endpoint reading the server log from the given tick with the given [`LogApi`].
+/// Further query parameters can be appended to it
+fn log_endpoint(api: LogApi, from: &str, last_scanned: u64) -> String {
+	match api {
+		LogApi::LoggerFollow => format!("/_api/replication/logger-follow?from={}", from),
+		LogApi::WalTail => format!("/_api/wal/tail?from={}&lastScanned={}", from, last_scanned),
+	}
+}
+
+/// Processes one logger line, pushing into `batch` the [`Event`]s that are ready to be
+/// dispatched, keeping the document operations of the ongoing transactions of the [`LogState`]
+/// until they get committed. Document operations on collections the [`LogState`] doesn't know and
+/// malformed DDL operations are reported to the [`Observer`] and skipped, as are log types this
+/// crate doesn't know
+fn process_line(
+	log: &mut LogState,
+	line: String,
+	batch: &mut Vec<Event>,
+	observer: &Observer,
+) -> Result<()> {
+	// We do this kind of parsing with indexes and characters instead of serializing or
+	// deserializing JSON directly using `serde_json` because it'd consume a lot of resources
+	// for some operations that may not be needed to be parsed.
+
+	// Get index after search on line
+	fn find_idx(line: &str, search: &str) -> Result<usize> {
+		Ok(line
+			.find(search)
+			.ok_or(Error::new(Kind::Io(Io::Serialize)))?
+			+ search.len())
+	}
+
+	let type_idx = find_idx(line.as_str(), "\"type\":")?;
+
+	// Types this crate can't read are skipped below like the unknown ones
+	let log_type_str: u16 = utils::get_string_between(line.as_str(), type_idx, 4)
+		.parse()
+		.unwrap_or_default();
+
+	// Get transaction id
+	fn get_tid(line: &str) -> Result<String> {
+		let tid_idx = find_idx(line, "\"tid\":\"")?;
+
+		Ok(utils::get_string_until(line, tid_idx, '"'))
+	}
+
+	// Parse a document operation, skipping it if its collection is unknown
+	fn parse_document(
+		log: &mut LogState,
+		log_type: LogType,
+		line: &str,
+		observer: &Observer,
+	) -> Result<Option<Event>> {
+		let event = Event::parse(log_type, line, &mut log.collections)?;
+
+		if let Some(Event::InsertOrReplace(doc) | Event::Remove(doc)) = &event {
+			if doc.collection.is_empty() {
+				observer.report(
+					Severity::Error,
+					DiagnosticCategory::Parse,
+					format!(
+						"skipping document operation on unknown collection {}",
+						doc.cuid().unwrap_or("without name")
+					),
+				);
+
+				return Ok(None);
+			}
+		}
+
+		Ok(event)
+	}
+
+	if let Ok(log_type) = log_type_str.try_into() {
+		match log_type {
+			LogType::StartTransaction => {
+				let tid = get_tid(line.as_str())?;
+
+				let tick = get_line_tick(line.as_str())?.parse().map_crate_err()?;
+
+				log.transactions.insert(tid.clone(), Transaction::new(tick));
+			}
+			LogType::RemoveDocument | LogType::InsertOrReplaceDocument => {
+				let tid = get_tid(line.as_str())?;
+
+				// The field tid might contain the value “0” to identify a single operation
+				// that is not part of a multi-document transaction
+				if tid == "0" {
+					batch.extend(parse_document(log, log_type, line.as_str(), observer)?);
+				} else {
+					// If the transaction's id is not 0 and it's not on already started
+					// transactions we just ignore the operation as it shouldn't get parsed
+					if log.transactions.contains_key(tid.as_str()) {
+						let event = parse_document(log, log_type, line.as_str(), observer)?;
+
+						if let Some(t) = log.transactions.get_mut(tid.as_str()) {
+							t.events.extend(event)
+						}
+					}
+				}
+			}
+			LogType::CommitTransaction => {
+				let tid = get_tid(line.as_str())?;
+
+				if let Some(t) = log.transactions.remove(tid.as_str()) {
+					batch.extend(t.events);
+				}
+			}
+			LogType::AbortTransaction => {
+				let tid = get_tid(line.as_str())?;
+
+				log.transactions.remove(tid.as_str());
+			}
+			ddl => match Event::parse(ddl, line.as_str(), &mut log.collections) {
+				Ok(event) => batch.extend(event),
+				// A DDL operation the crate can't shape must not stop reading the log
+				Err(e) => observer.report(
+					Severity::Error,
+					DiagnosticCategory::Parse,
+					format!(
+						"skipping malformed {} operation at tick {}: {}",
+						log_type_str,
+						get_line_tick(line.as_str()).unwrap_or_default(),
+						e
+					),
+				),
+			},
+		}
+	}
+
+	Ok(())
+}
+
+/// Ongoing catch-up with the server log, see [`Trigger::on_catch_up_progress`]
+struct CatchUp {
+	/// The tick the catch-up started from
+	tick: u64,
+	/// The dispatched events count when the catch-up started
+	events: u64,
+	/// The time and tick the rate was last measured at
+	sample: (Instant, u64),
+	/// Exponentially weighted average of the ticks advanced per second
+	rate: Option<f64>,
+	/// The time the progress was last reported
+	reported: Instant,
+}
+
+/// Collection silence watchdog, see [`Trigger::on_collection_silence`]
+struct SilenceWatchdog {
+	collection: String,
+	window: Duration,
+	last_event: Instant,
+	alerted: bool,
+	callback: SilenceCallback,
+}
+
+/// State of the server log reading carried from one line to the next
+#[derive(Default)]
+pub(crate) struct LogState {
+	/// The ongoing [`Transaction`]s indexed by id
+	transactions: HashMap<String, Transaction>,
+	/// The collection names **`wal/tail`** lines are resolved with
+	collections: CollectionNames,
+}
+
+impl LogState {
+	/// Returns the tick the server log can be read again from without losing the operations of
+	/// the ongoing [`Transaction`]s, which are only kept in memory: the given last processed tick,
+	/// or the one right before the earliest ongoing transaction started
+	fn resume_tick(&self, last_tick: &str) -> String {
+		let start = match self.transactions.values().map(|t| t.tick).min() {
+			Some(start) => start.saturating_sub(1),
+			None => return last_tick.to_string(),
+		};
+
+		match last_tick.parse::<u64>() {
+			Ok(last) if last <= start => last_tick.to_string(),
+			_ => start.to_string(),
+		}
+	}
+
+	/// Returns the ongoing [`Transaction`]s to be kept on a [`ResumeToken`]
+	fn open_transactions(&self) -> Vec<OpenTransaction> {
+		let mut transactions = self
+			.transactions
+			.iter()
+			.map(|(tid, t)| OpenTransaction {
+				tid: tid.clone(),
+				tick: t.tick.to_string(),
+				events: t
+					.events
+					.iter()
+					.filter_map(|e| serde_json::to_value(e).ok())
+					.collect(),
+			})
+			.collect::<Vec<_>>();
+
+		transactions.sort_by_key(|t| t.tick.parse::<u64>().unwrap_or_default());
+		transactions
+	}
+
+	/// Replaces the ongoing [`Transaction`]s with the ones kept on a [`ResumeToken`]
+	fn restore(&mut self, transactions: Vec<OpenTransaction>) -> Result<()> {
+		self.transactions.clear();
+
+		for t in transactions {
+			let mut transaction = Transaction::new(t.tick.parse().map_crate_err()?);
+
+			// Document data is kept raw, which serde can't read back through the tagged Event, so
+			// the document operations are read on their own
+			for event in t.events {
+				let doc: DocumentOperation = serde_json::from_str(
+					event
+						.get("operation")
+						.map(JsonValue::to_string)
+						.unwrap_or_default()
+						.as_str(),
+				)
+				.map_crate_err()?;
+
+				transaction
+					.events
+					.push(match event.get("type").and_then(JsonValue::as_str) {
+						Some("remove") => Event::Remove(doc),
+						_ => Event::InsertOrReplace(doc),
+					});
+			}
+
+			self.transactions.insert(t.tid, transaction);
+		}
+
+		Ok(())
 	}
 }
 
-/// Transactions are those who specify in themselves multiple [`TransactionOperation`] this is used
-/// because ArangoDB works in the way that they start transactions, add operations to that
-/// transaction and then abort or commit the transaction. We need to keep track of which
-/// [`TransactionOperation`] belongs to which [`Transaction`] so that we can abort them or execute
-/// them on commit.
+/// Transactions are those who specify in themselves multiple document operations [`Event`]s this
+/// is used because ArangoDB works in the way that they start transactions, add operations to that
+/// transaction and then abort or commit the transaction. We need to keep track of which [`Event`]
+/// belongs to which [`Transaction`] so that we can abort them or execute them on commit.
 pub(crate) struct Transaction {
-	operations: Vec<TransactionOperation>,
+	/// The tick the transaction started at
+	tick: u64,
+	events: Vec<Event>,
 }
 
 impl Transaction {
-	/// Creates a new empty [`Transaction`]
-	pub(crate) fn empty() -> Self {
+	/// Creates a new empty [`Transaction`] started at the given tick
+	pub(crate) fn new(tick: u64) -> Self {
 		Self {
-			operations: Vec::new(),
+			tick,
+			events: Vec::new(),
 		}
 	}
 }
-
-/// Insert or Replace/Remove operations that can or not belong to a [`Transaction`] if they don't
-/// belong to a [`Transaction`] it gets executed at the same moment it is parsed.
-pub(crate) enum TransactionOperation {
-	InsertOrReplaceDocument(DocumentOperation),
-	RemoveDocument(DocumentOperation),
-}
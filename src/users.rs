@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the system collection holding the ArangoDB users. It only exists on the `_system`
+/// database, so only a [`Trigger`](`crate::Trigger`) listening to it receives user changes
+pub const USERS_COLLECTION: &str = "_users";
+
+/// Read and write permissions of a user on a database or a collection. Having neither of them
+/// means no access
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Permissions {
+	#[serde(default)]
+	pub read: bool,
+	#[serde(default)]
+	pub write: bool,
+}
+
+/// Permissions of a user on a database and its collections. The `*` key stands for every
+/// database or collection without explicit permissions
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DatabaseAccess {
+	#[serde(default)]
+	pub permissions: Permissions,
+	#[serde(default)]
+	pub collections: HashMap<String, CollectionAccess>,
+}
+
+/// Permissions of a user on a collection
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CollectionAccess {
+	#[serde(default)]
+	pub permissions: Permissions,
+}
+
+/// Authentication data of a user. Password hashes are not decoded
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UserAuthData {
+	#[serde(default)]
+	pub active: bool,
+}
+
+/// Document of the `_users` system collection
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UserDocument {
+	#[serde(rename = "_key")]
+	pub key: String,
+	/// The user name
+	pub user: String,
+	#[serde(rename = "authData", default)]
+	pub auth_data: UserAuthData,
+	/// The [`DatabaseAccess`] indexed by database name
+	#[serde(default)]
+	pub databases: HashMap<String, DatabaseAccess>,
+}
+
+impl UserDocument {
+	/// Returns every permission granted to the user, one [`Grant`] per database and per
+	/// collection with any access
+	///
+	/// returns: `Vec<Grant>`
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use arangodb_events_rs::UserDocument;
+	///
+	/// let user: UserDocument = serde_json::from_str(
+	///     r#"{
+	///         "_key": "123",
+	///         "user": "alice",
+	///         "authData": { "active": true },
+	///         "databases": {
+	///             "alchemy": { "permissions": { "read": true, "write": false } }
+	///         }
+	///     }"#,
+	/// )
+	/// .unwrap();
+	///
+	/// let grants = user.grants();
+	///
+	/// assert_eq!(grants.len(), 1);
+	/// assert!(grants[0].permissions.read && !grants[0].permissions.write);
+	/// ```
+	pub fn grants(&self) -> Vec<Grant> {
+		let mut grants = Vec::new();
+
+		for (database, access) in &self.databases {
+			if access.permissions != Permissions::default() {
+				grants.push(Grant {
+					database: database.clone(),
+					collection: None,
+					permissions: access.permissions,
+				});
+			}
+
+			for (collection, access) in &access.collections {
+				if access.permissions != Permissions::default() {
+					grants.push(Grant {
+						database: database.clone(),
+						collection: Some(collection.clone()),
+						permissions: access.permissions,
+					});
+				}
+			}
+		}
+
+		grants
+	}
+}
+
+/// Permissions of a user on a database, or on a collection of it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grant {
+	pub database: String,
+	/// The collection name, [`None`] for database-level permissions
+	pub collection: Option<String>,
+	pub permissions: Permissions,
+}
+
+/// Change of the `_users` system collection, decoded from its document operations. As the
+/// replication log doesn't tell inserts and replaces apart, the [`Trigger`](`crate::Trigger`)
+/// keeps the last version of each user it received to tell what changed. Users not received since
+/// the Trigger was created have no previous version, so all their permissions show up as granted
+#[derive(Clone, Debug)]
+pub enum UserEvent {
+	/// A user got created or updated
+	Saved {
+		user: UserDocument,
+		previous: Option<UserDocument>,
+	},
+	/// A user got removed. Only its key comes along the removal
+	Removed {
+		key: String,
+		previous: Option<UserDocument>,
+	},
+}
+
+impl UserEvent {
+	/// Returns the `_key` of the user document
+	///
+	/// returns: `&str`
+	pub fn key(&self) -> &str {
+		match self {
+			Self::Saved { user, .. } => user.key.as_str(),
+			Self::Removed { key, .. } => key.as_str(),
+		}
+	}
+
+	/// Returns the permissions the user didn't have before this change, including the upgraded
+	/// ones, e.g. from read-only to read-write
+	///
+	/// returns: `Vec<Grant>`
+	pub fn granted(&self) -> Vec<Grant> {
+		match self {
+			Self::Saved { user, previous } => {
+				let before = previous.as_ref().map(|p| p.grants()).unwrap_or_default();
+
+				user.grants()
+					.into_iter()
+					.filter(|g| !before.contains(g))
+					.collect()
+			}
+			Self::Removed { .. } => Vec::new(),
+		}
+	}
+
+	/// Returns the permissions the user had before this change and lost, including the
+	/// downgraded ones. Every previous permission is revoked when the user gets removed
+	///
+	/// returns: `Vec<Grant>`
+	pub fn revoked(&self) -> Vec<Grant> {
+		let (after, previous) = match self {
+			Self::Saved { user, previous } => (user.grants(), previous),
+			Self::Removed { previous, .. } => (Vec::new(), previous),
+		};
+
+		previous
+			.as_ref()
+			.map(|p| p.grants())
+			.unwrap_or_default()
+			.into_iter()
+			.filter(|g| !after.contains(g))
+			.collect()
+	}
+}
@@ -11,7 +11,7 @@
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// let data = "My car is red";
 ///
 /// let out = get_string_between(data, 3, 6);
@@ -34,7 +34,7 @@ pub(crate) fn get_string_between(str: &str, start: usize, count: usize) -> Strin
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// let data = "My car is red";
 ///
 /// let out = get_string_until(data, 3, 'e');
@@ -0,0 +1,141 @@
+// Each test crate only uses a part of the stub
+#![allow(dead_code)]
+
+use arangodb_events_rs::api::Event;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Last log tick reported by the stub `logger-state` endpoint
+pub const START_TICK: &str = "100";
+
+/// Responds to a request given its path and query, or returns [`None`] to fall back to the
+/// endpoints every Trigger calls on init
+pub type Route = dyn Fn(&str) -> Option<Response<Body>> + Send + Sync;
+
+/// ArangoDB HTTP server stub recording the path and query of every request it gets
+pub struct StubServer {
+	pub host: String,
+	requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+	/// Starts a stub reporting the given server version, answering the requests the route doesn't
+	/// handle with an empty database: no collections and a log at [`START_TICK`]
+	pub fn start<F>(version: &str, route: F) -> Self
+	where
+		F: Fn(&str) -> Option<Response<Body>> + Send + Sync + 'static,
+	{
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let route: Arc<Route> = Arc::new(route);
+		let version = format!(r#"{{"server":"arango","version":"{}"}}"#, version);
+
+		let log = requests.clone();
+
+		let make_service = make_service_fn(move |_| {
+			let (route, log, version) = (route.clone(), log.clone(), version.clone());
+
+			async move {
+				Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+					let uri = req
+						.uri()
+						.path_and_query()
+						.map(|p| p.to_string())
+						.unwrap_or_default();
+
+					log.lock().unwrap().push(uri.clone());
+
+					let response = route(uri.as_str())
+						.unwrap_or_else(|| fallback(uri.as_str(), version.as_str()));
+
+					async move { Ok::<_, Infallible>(response) }
+				}))
+			}
+		});
+
+		let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+		let host = format!("http://{}", server.local_addr());
+
+		tokio::spawn(server);
+
+		Self { host, requests }
+	}
+
+	/// Returns the path and query of the requests received so far
+	pub fn requests(&self) -> Vec<String> {
+		self.requests.lock().unwrap().clone()
+	}
+}
+
+/// Responds to the endpoints every Trigger calls on init
+fn fallback(uri: &str, version: &str) -> Response<Body> {
+	let path = uri.split('?').next().unwrap_or_default();
+
+	if path.ends_with("/_api/version") {
+		json(version)
+	} else if path.ends_with("/_api/replication/logger-state") {
+		json(&format!(
+			r#"{{"state":{{"running":true,"lastLogTick":"{0}","lastUncommittedLogTick":"{0}","totalEvents":0,"time":""}},"server":{{"version":"3.11.1","serverId":"1"}}}}"#,
+			START_TICK
+		))
+	} else if path.ends_with("/_api/collection") {
+		json(r#"{"result":[]}"#)
+	} else {
+		Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(Body::from(r#"{"error":true,"errorNum":404}"#))
+			.unwrap()
+	}
+}
+
+/// Builds a `200 OK` JSON response
+pub fn json(body: &str) -> Response<Body> {
+	Response::new(Body::from(body.to_string()))
+}
+
+/// Builds a server log response with the given replication headers and body
+pub fn log_response(headers: &[(&str, &str)], body: &str) -> Response<Body> {
+	let mut response = Response::builder().status(if body.is_empty() {
+		StatusCode::NO_CONTENT
+	} else {
+		StatusCode::OK
+	});
+
+	for (name, value) in headers {
+		response = response.header(*name, *value);
+	}
+
+	response.body(Body::from(body.to_string())).unwrap()
+}
+
+/// Describes an [`Event`] as `kind collection [key]` to compare it easily
+pub fn describe(event: &Event) -> String {
+	match event {
+		Event::InsertOrReplace(doc) | Event::Remove(doc) => format!(
+			"{} {} {}",
+			if matches!(event, Event::Remove(_)) {
+				"remove"
+			} else {
+				"save"
+			},
+			doc.collection,
+			doc.field("_key")
+				.and_then(|k| k.as_str())
+				.unwrap_or_default()
+		),
+		Event::Collection(op) => format!(
+			"{:?} {}{}",
+			op.kind,
+			op.collection.as_deref().unwrap_or_default(),
+			op.new_name().map(|n| format!(" {}", n)).unwrap_or_default()
+		),
+		Event::Index(op) => format!(
+			"index {:?} {}",
+			op.kind,
+			op.collection.as_deref().unwrap_or_default()
+		),
+		event => format!("{:?}", event),
+	}
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use arangodb_events_rs::api::DocumentOperation;
+use arangodb_events_rs::{
+	AsyncHandlerOutput, DiagnosticCategory, DispatchOutcome, Handler, HandlerContext,
+	HandlerContextFactory, HandlerEvent, SkipReason, Trigger,
+};
+use common::{describe, log_response, StubServer};
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+/// Records the keys of the documents it gets called with
+struct KeyHandler;
+
+type Keys = Arc<Mutex<Vec<String>>>;
+
+impl KeyHandler {
+	fn record(keys: &Keys, doc: &DocumentOperation) {
+		let key = doc
+			.field("_key")
+			.and_then(|k| k.as_str())
+			.unwrap_or_default();
+
+		keys.lock()
+			.unwrap()
+			.push(format!("{} {}", doc.collection, key));
+	}
+}
+
+impl Handler for KeyHandler {
+	type Context = Keys;
+
+	#[cfg(feature = "async")]
+	fn call<'a>(keys: &'a Keys, doc: &'a DocumentOperation) -> AsyncHandlerOutput<'a> {
+		Box::pin(async move { Self::record(keys, doc) })
+	}
+
+	#[cfg(not(feature = "async"))]
+	fn call(keys: &Keys, doc: &DocumentOperation) {
+		Self::record(keys, doc)
+	}
+}
+
+/// Overrides its dispatch to never return a future
+struct SilentHandler;
+
+impl Handler for SilentHandler {
+	type Context = Keys;
+
+	#[cfg(feature = "async")]
+	fn call<'a>(keys: &'a Keys, doc: &'a DocumentOperation) -> AsyncHandlerOutput<'a> {
+		Box::pin(async move { KeyHandler::record(keys, doc) })
+	}
+
+	#[cfg(not(feature = "async"))]
+	fn call(keys: &Keys, doc: &DocumentOperation) {
+		KeyHandler::record(keys, doc)
+	}
+
+	fn dispatch<'a>(
+		_: &'a HandlerContext<dyn Any + Send + Sync>,
+		_: &'a DocumentOperation,
+	) -> Option<AsyncHandlerOutput<'a>> {
+		None
+	}
+}
+
+#[tokio::test]
+async fn priorities_never_move_documents_across_a_rename() {
+	let stub = StubServer::start("3.7.1", |uri| {
+		if uri.contains("/_api/replication/logger-follow?from=100") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "104"),
+					("X-Arango-Replication-Checkmore", "false"),
+				],
+				concat!(
+					r#"{"tick":"101","type":2300,"tid":"0","cname":"x","data":{"_key":"1","_rev":"_gWj1a1---"}}"#,
+					"\n",
+					r#"{"tick":"102","type":2300,"tid":"0","cname":"z","data":{"_key":"2","_rev":"_gWj1a2---"}}"#,
+					"\n",
+					r#"{"tick":"103","type":2002,"cname":"x","data":{"name":"y","oldName":"x"}}"#,
+					"\n",
+					r#"{"tick":"104","type":2300,"tid":"0","cname":"y","data":{"_key":"3","_rev":"_gWj1a3---"}}"#,
+				),
+			))
+		} else {
+			None
+		}
+	});
+
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let keys = Keys::default();
+
+	let sink = events.clone();
+
+	let mut trigger = Trigger::new(stub.host.as_str(), "shop")
+		.with_collection_priority("y", 10)
+		.with_collection_priority("z", 5)
+		.with_rename_tracking(true)
+		.on_event(move |event| sink.lock().unwrap().push(describe(event)));
+
+	trigger
+		.subscribe_to::<KeyHandler>(
+			HandlerEvent::InsertOrReplace,
+			"x",
+			HandlerContextFactory::from(keys.clone()),
+		)
+		.unwrap();
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	// Documents are only sorted by priority up to the rename, which stays in place
+	assert_eq!(
+		*events.lock().unwrap(),
+		vec!["save z 2", "save x 1", "Rename x y", "save y 3"]
+	);
+	assert_eq!(*keys.lock().unwrap(), vec!["x 1", "y 3"]);
+}
+
+#[tokio::test]
+async fn dispatch_without_future_is_skipped() {
+	let stub = StubServer::start("3.7.1", |uri| {
+		if uri.contains("/_api/replication/logger-follow?from=100") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "101"),
+					("X-Arango-Replication-Checkmore", "false"),
+				],
+				r#"{"tick":"101","type":2300,"tid":"0","cname":"x","data":{"_key":"1","_rev":"_gWj1a1---"}}"#,
+			))
+		} else {
+			None
+		}
+	});
+
+	let outcomes = Arc::new(Mutex::new(Vec::new()));
+	let diagnostics = Arc::new(Mutex::new(Vec::new()));
+	let keys = Keys::default();
+
+	let (outcomes_sink, diagnostics_sink) = (outcomes.clone(), diagnostics.clone());
+
+	let mut trigger = Trigger::new(stub.host.as_str(), "shop")
+		.on_dispatch(move |report| {
+			for handler in &report.handlers {
+				outcomes_sink.lock().unwrap().push(handler.outcome);
+			}
+		})
+		.on_diagnostic(move |d| {
+			if d.category == DiagnosticCategory::Handler {
+				diagnostics_sink.lock().unwrap().push(d.message.clone());
+			}
+		});
+
+	trigger
+		.subscribe_to::<SilentHandler>(
+			HandlerEvent::InsertOrReplace,
+			"x",
+			HandlerContextFactory::from(keys.clone()),
+		)
+		.unwrap();
+	trigger
+		.subscribe_to::<KeyHandler>(
+			HandlerEvent::InsertOrReplace,
+			"x",
+			HandlerContextFactory::from(keys.clone()),
+		)
+		.unwrap();
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	// The other subscriptions still get called
+	assert_eq!(*keys.lock().unwrap(), vec!["x 1"]);
+	assert_eq!(
+		*outcomes.lock().unwrap(),
+		vec![
+			DispatchOutcome::Skipped(SkipReason::ContextMismatch),
+			DispatchOutcome::Called
+		]
+	);
+	assert_eq!(diagnostics.lock().unwrap().len(), 1);
+}
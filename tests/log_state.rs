@@ -0,0 +1,362 @@
+mod common;
+
+use arangodb_events_rs::{DiagnosticCategory, LogApi, Result, ResumeToken, TickStore, Trigger};
+use common::{describe, json, log_response, StubServer, START_TICK};
+use std::sync::{Arc, Mutex};
+
+/// Builds a `wal/tail` Trigger on the stub, recording the description of every event
+fn wal_tail_trigger(stub: &StubServer) -> (Trigger, Arc<Mutex<Vec<String>>>) {
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let sink = events.clone();
+
+	let trigger = Trigger::new(stub.host.as_str(), "shop")
+		.with_log_api(LogApi::WalTail)
+		.on_event(move |event| sink.lock().unwrap().push(describe(event)));
+
+	(trigger, events)
+}
+
+/// Records the ticks of the tokens it saves
+struct RecordingStore(Arc<Mutex<Vec<String>>>);
+
+impl TickStore for RecordingStore {
+	fn load(&self) -> Result<Option<ResumeToken>> {
+		Ok(None)
+	}
+
+	fn save(&self, token: &ResumeToken) -> Result<()> {
+		self.0.lock().unwrap().push(token.tick.clone());
+		Ok(())
+	}
+}
+
+/// Headers of a response which included nothing
+fn nothing_included<'a>(check_more: &'a str, last_scanned: &'a str) -> [(&'a str, &'a str); 5] {
+	[
+		("X-Arango-Replication-Lastincluded", "0"),
+		("X-Arango-Replication-Lastscanned", last_scanned),
+		("X-Arango-Replication-Lasttick", "200"),
+		("X-Arango-Replication-Checkmore", check_more),
+		("X-Arango-Replication-Frompresent", "true"),
+	]
+}
+
+#[tokio::test]
+async fn empty_result_with_check_more_resumes_from_last_scanned() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/wal/tail?from=100&lastScanned=0") {
+			Some(log_response(&nothing_included("true", "150"), ""))
+		} else if uri.contains("/_api/wal/tail?from=100&lastScanned=150") {
+			Some(log_response(&nothing_included("false", "200"), ""))
+		} else {
+			None
+		}
+	});
+
+	let (mut trigger, events) = wal_tail_trigger(&stub);
+
+	trigger.init().await.unwrap();
+
+	// Scanning further without including anything goes on right away without an event
+	trigger.listen().await.unwrap();
+	assert!(events.lock().unwrap().is_empty());
+	assert_eq!(trigger.resume_token().tick, START_TICK);
+
+	trigger.listen().await.unwrap();
+	assert_eq!(*events.lock().unwrap(), vec!["Heartbeat"]);
+	assert_eq!(trigger.resume_token().tick, START_TICK);
+
+	let tails = stub
+		.requests()
+		.into_iter()
+		.filter(|uri| uri.contains("/_api/wal/tail"))
+		.collect::<Vec<_>>();
+
+	assert_eq!(tails.len(), 2);
+	assert!(tails[1].contains("from=100&lastScanned=150"));
+}
+
+#[tokio::test]
+async fn nothing_included_without_check_more_is_a_heartbeat() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/wal/tail") {
+			Some(log_response(&nothing_included("false", "100"), ""))
+		} else {
+			None
+		}
+	});
+
+	let (mut trigger, events) = wal_tail_trigger(&stub);
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	assert_eq!(*events.lock().unwrap(), vec!["Heartbeat"]);
+	assert_eq!(trigger.resume_token().tick, START_TICK);
+}
+
+#[tokio::test]
+async fn pruned_tick_is_reported_as_expired() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/wal/tail") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "180"),
+					("X-Arango-Replication-Lastscanned", "180"),
+					("X-Arango-Replication-Lasttick", "200"),
+					("X-Arango-Replication-Checkmore", "true"),
+					("X-Arango-Replication-Frompresent", "false"),
+				],
+				r#"{"tick":"180","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"0","data":{"_key":"1","_rev":"_gWj1b1---"}}"#,
+			))
+		} else {
+			None
+		}
+	});
+
+	let (mut trigger, events) = wal_tail_trigger(&stub);
+
+	trigger.init().await.unwrap();
+
+	let error = trigger.listen().await.unwrap_err();
+
+	assert!(error.is_tick_expired());
+	assert!(events.lock().unwrap().is_empty());
+	assert_eq!(trigger.resume_token().tick, START_TICK);
+}
+
+#[tokio::test]
+async fn transaction_spanning_responses_is_dispatched_on_commit() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/collection") {
+			Some(json(
+				r#"{"result":[{"id":"150","name":"orders","globallyUniqueId":"h1A2B3C4D5E6F/150"}]}"#,
+			))
+		} else if uri.contains("/_api/wal/tail?from=100&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "102"),
+					("X-Arango-Replication-Lastscanned", "102"),
+					("X-Arango-Replication-Lasttick", "103"),
+					("X-Arango-Replication-Checkmore", "true"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				concat!(
+					r#"{"tick":"101","type":2200,"db":"shop","tid":"300"}"#,
+					"\n",
+					r#"{"tick":"102","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"300","data":{"_key":"10","_rev":"_gWj1a4---"}}"#,
+				),
+			))
+		} else if uri.contains("/_api/wal/tail?from=102&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "103"),
+					("X-Arango-Replication-Lastscanned", "103"),
+					("X-Arango-Replication-Lasttick", "103"),
+					("X-Arango-Replication-Checkmore", "false"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				r#"{"tick":"103","type":2201,"db":"shop","tid":"300"}"#,
+			))
+		} else {
+			None
+		}
+	});
+
+	let (mut trigger, events) = wal_tail_trigger(&stub);
+
+	trigger.init().await.unwrap();
+
+	// The document operation stays buffered until its transaction is committed
+	trigger.listen().await.unwrap();
+	assert!(events.lock().unwrap().is_empty());
+
+	trigger.listen().await.unwrap();
+	assert_eq!(*events.lock().unwrap(), vec!["save orders 10"]);
+	assert_eq!(trigger.resume_token().tick, "103");
+}
+
+#[tokio::test]
+async fn malformed_and_unknown_lines_are_skipped() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/collection") {
+			Some(json(
+				r#"{"result":[{"id":"150","name":"orders","globallyUniqueId":"h1A2B3C4D5E6F/150"}]}"#,
+			))
+		} else if uri.contains("/_api/wal/tail?from=100&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "103"),
+					("X-Arango-Replication-Lastscanned", "103"),
+					("X-Arango-Replication-Lasttick", "103"),
+					("X-Arango-Replication-Checkmore", "false"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				concat!(
+					r#"{"tick":"101","type":2000,"db":"shop","cuid":"h1A2B3C4D5E6F/201","cname":7}"#,
+					"\n",
+					r#"{"tick":"102","type":9999,"db":"shop"}"#,
+					"\n",
+					r#"{"tick":"103","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"0","data":{"_key":"10","_rev":"_gWj1a4---"}}"#,
+				),
+			))
+		} else {
+			None
+		}
+	});
+
+	let (trigger, events) = wal_tail_trigger(&stub);
+	let errors = Arc::new(Mutex::new(Vec::new()));
+	let sink = errors.clone();
+
+	let mut trigger = trigger.on_diagnostic(move |d| {
+		if d.category == DiagnosticCategory::Parse {
+			sink.lock().unwrap().push(d.message.clone());
+		}
+	});
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	assert_eq!(*events.lock().unwrap(), vec!["save orders 10"]);
+	assert_eq!(trigger.resume_token().tick, "103");
+
+	let errors = errors.lock().unwrap();
+
+	assert_eq!(errors.len(), 1);
+	assert!(errors[0].contains("tick 101"));
+}
+
+#[tokio::test]
+async fn open_transactions_hold_back_the_saved_tick() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/collection") {
+			Some(json(
+				r#"{"result":[{"id":"150","name":"orders","globallyUniqueId":"h1A2B3C4D5E6F/150"}]}"#,
+			))
+		} else if uri.contains("/_api/wal/tail?from=100&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "104"),
+					("X-Arango-Replication-Lastscanned", "104"),
+					("X-Arango-Replication-Lasttick", "105"),
+					("X-Arango-Replication-Checkmore", "true"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				concat!(
+					r#"{"tick":"101","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"0","data":{"_key":"9","_rev":"_gWj1a3---"}}"#,
+					"\n",
+					r#"{"tick":"102","type":2200,"db":"shop","tid":"300"}"#,
+					"\n",
+					r#"{"tick":"103","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"300","data":{"_key":"10","_rev":"_gWj1a4---"}}"#,
+					"\n",
+					r#"{"tick":"104","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"0","data":{"_key":"11","_rev":"_gWj1a5---"}}"#,
+				),
+			))
+		} else if uri.contains("/_api/wal/tail?from=104&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "105"),
+					("X-Arango-Replication-Lastscanned", "105"),
+					("X-Arango-Replication-Lasttick", "105"),
+					("X-Arango-Replication-Checkmore", "false"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				r#"{"tick":"105","type":2201,"db":"shop","tid":"300"}"#,
+			))
+		} else {
+			None
+		}
+	});
+
+	let (trigger, events) = wal_tail_trigger(&stub);
+	let saved = Arc::new(Mutex::new(Vec::new()));
+
+	let mut trigger = trigger.with_tick_store(RecordingStore(saved.clone()));
+
+	trigger.init().await.unwrap();
+
+	// Resuming past the transaction start would lose its buffered operations
+	trigger.listen().await.unwrap();
+	assert_eq!(
+		*events.lock().unwrap(),
+		vec!["save orders 9", "save orders 11"]
+	);
+	assert_eq!(trigger.resume_token().tick, "101");
+
+	trigger.listen().await.unwrap();
+	assert_eq!(
+		*events.lock().unwrap(),
+		vec!["save orders 9", "save orders 11", "save orders 10"]
+	);
+	assert_eq!(trigger.resume_token().tick, "105");
+	assert_eq!(*saved.lock().unwrap(), vec!["101", "105"]);
+}
+
+#[tokio::test]
+async fn open_transactions_are_restored_from_the_resume_token() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/collection") {
+			Some(json(
+				r#"{"result":[{"id":"150","name":"orders","globallyUniqueId":"h1A2B3C4D5E6F/150"}]}"#,
+			))
+		} else if uri.contains("/_api/wal/tail?from=100&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "103"),
+					("X-Arango-Replication-Lastscanned", "103"),
+					("X-Arango-Replication-Lasttick", "104"),
+					("X-Arango-Replication-Checkmore", "true"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				concat!(
+					r#"{"tick":"101","type":2200,"db":"shop","tid":"300"}"#,
+					"\n",
+					r#"{"tick":"102","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"300","data":{"_key":"10","_rev":"_gWj1a4---"}}"#,
+					"\n",
+					r#"{"tick":"103","type":2300,"db":"shop","cuid":"h1A2B3C4D5E6F/150","tid":"0","data":{"_key":"11","_rev":"_gWj1a5---"}}"#,
+				),
+			))
+		} else if uri.contains("/_api/wal/tail?from=103&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "104"),
+					("X-Arango-Replication-Lastscanned", "104"),
+					("X-Arango-Replication-Lasttick", "104"),
+					("X-Arango-Replication-Checkmore", "false"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				r#"{"tick":"104","type":2201,"db":"shop","tid":"300"}"#,
+			))
+		} else {
+			None
+		}
+	});
+
+	let (trigger, events) = wal_tail_trigger(&stub);
+	let mut trigger = trigger.with_transaction_checkpoint(true);
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	assert_eq!(*events.lock().unwrap(), vec!["save orders 11"]);
+
+	let stored = serde_json::to_string(&trigger.resume_token()).unwrap();
+	let token: ResumeToken = serde_json::from_str(&stored).unwrap();
+
+	assert_eq!(token.tick, "103");
+	assert_eq!(token.transactions.len(), 1);
+	assert_eq!(token.transactions[0].tick, "101");
+
+	// A restarted Trigger commits the transaction without reading its start again
+	let (trigger, events) = wal_tail_trigger(&stub);
+	let mut trigger = trigger
+		.with_transaction_checkpoint(true)
+		.with_resume_token(token);
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	assert_eq!(*events.lock().unwrap(), vec!["save orders 10"]);
+	assert!(trigger.resume_token().transactions.is_empty());
+}
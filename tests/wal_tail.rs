@@ -0,0 +1,77 @@
+mod common;
+
+use arangodb_events_rs::{LogApi, Severity, Trigger};
+use common::{describe, json, log_response, StubServer};
+use std::sync::{Arc, Mutex};
+
+const FIXTURE: &str = include_str!("fixtures/wal_tail.jsonl");
+
+#[tokio::test]
+async fn wal_tail_lines_are_resolved_by_collection_id() {
+	let stub = StubServer::start("3.11.1", |uri| {
+		if uri.contains("/_api/collection") {
+			Some(json(
+				r#"{"result":[{"id":"150","name":"orders","globallyUniqueId":"h1A2B3C4D5E6F/150"}]}"#,
+			))
+		} else if uri.contains("/_api/wal/tail") && uri.contains("from=100&") {
+			Some(log_response(
+				&[
+					("X-Arango-Replication-Lastincluded", "117"),
+					("X-Arango-Replication-Lastscanned", "117"),
+					("X-Arango-Replication-Lasttick", "117"),
+					("X-Arango-Replication-Checkmore", "false"),
+					("X-Arango-Replication-Frompresent", "true"),
+				],
+				FIXTURE,
+			))
+		} else {
+			None
+		}
+	});
+
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let errors = Arc::new(Mutex::new(Vec::new()));
+
+	let (events_sink, errors_sink) = (events.clone(), errors.clone());
+
+	let mut trigger = Trigger::new(stub.host.as_str(), "shop")
+		.with_log_api(LogApi::WalTail)
+		.on_event(move |event| events_sink.lock().unwrap().push(describe(event)))
+		.on_diagnostic(move |d| {
+			if d.severity == Severity::Error {
+				errors_sink.lock().unwrap().push(d.message.clone());
+			}
+		});
+
+	trigger.init().await.unwrap();
+	trigger.listen().await.unwrap();
+
+	assert_eq!(
+		*events.lock().unwrap(),
+		vec![
+			"Create products",
+			"save products 1",
+			"save orders 9",
+			"save orders 10",
+			"remove products 1",
+			"index Create orders",
+			"Rename products items",
+			"save items 2",
+			"Drop items",
+			"Truncate orders",
+		]
+	);
+
+	let errors = errors.lock().unwrap();
+
+	// The unknown collection and the dropped one
+	assert_eq!(errors.len(), 2);
+	assert!(errors[0].contains("h1A2B3C4D5E6F/999"));
+	assert!(errors[1].contains("h1A2B3C4D5E6F/201"));
+
+	assert_eq!(trigger.resume_token().tick, "117");
+	assert!(stub
+		.requests()
+		.iter()
+		.any(|uri| uri.ends_with("/_db/shop/_api/collection")));
+}